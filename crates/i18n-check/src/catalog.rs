@@ -0,0 +1,140 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use crate::placeholders::variables;
+use crate::Problem;
+
+/// Language every other translation file is compared with.
+pub const REFERENCE: &str = "en";
+
+/// Messages of a translation file, by key.
+#[derive(Debug, Default)]
+pub struct Catalog {
+    pub language: String,
+    pub messages: BTreeMap<String, String>,
+}
+
+/// Read every `<language>.json` file in `dir`, sorted by language. Files that
+/// can't be read are reported as problems.
+pub fn load(dir: &Path, problems: &mut Vec<Problem>) -> Vec<Catalog> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            problems.push(Problem::Unreadable(
+                dir.display().to_string(),
+                err.to_string(),
+            ));
+            return Vec::new();
+        },
+    };
+
+    let mut catalogs = Vec::new();
+    for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let language = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(language) => language.to_string(),
+            None => continue,
+        };
+
+        let messages = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| serde_json::from_str(&text).map_err(|err| err.to_string()));
+        match messages {
+            Ok(messages) => catalogs.push(Catalog { language, messages }),
+            Err(err) => problems.push(Problem::Unreadable(path.display().to_string(), err)),
+        }
+    }
+    catalogs.sort_by(|a, b| a.language.cmp(&b.language));
+    catalogs
+}
+
+/// Compare every catalog with the reference one: keys must be the same, and
+/// messages must use the same variables.
+pub fn compare(catalogs: &[Catalog], problems: &mut Vec<Problem>) {
+    let reference = match catalogs.iter().find(|c| c.language == REFERENCE) {
+        Some(reference) => reference,
+        None => {
+            problems.push(Problem::MissingReference(REFERENCE.to_string()));
+            return;
+        },
+    };
+
+    for catalog in catalogs.iter().filter(|c| c.language != REFERENCE) {
+        for (key, message) in &reference.messages {
+            let translated = match catalog.messages.get(key) {
+                Some(translated) => translated,
+                None => {
+                    problems.push(Problem::MissingKey(catalog.language.clone(), key.clone()));
+                    continue;
+                },
+            };
+
+            let expected = variables(message);
+            let found = variables(translated);
+            if expected != found {
+                problems.push(Problem::Placeholders {
+                    language: catalog.language.clone(),
+                    key: key.clone(),
+                    missing: expected.difference(&found).cloned().collect(),
+                    unknown: found.difference(&expected).cloned().collect(),
+                });
+            }
+        }
+
+        let extra: BTreeSet<_> = catalog
+            .messages
+            .keys()
+            .filter(|key| !reference.messages.contains_key(*key))
+            .collect();
+        for key in extra {
+            problems.push(Problem::ExtraKey(catalog.language.clone(), key.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, Catalog};
+    use crate::Problem;
+
+    fn catalog(language: &str, messages: &[(&str, &str)]) -> Catalog {
+        Catalog {
+            language: language.to_string(),
+            messages: messages
+                .iter()
+                .map(|(key, message)| (key.to_string(), message.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn keys_and_placeholders() {
+        let catalogs = [
+            catalog("en", &[("a.b", "Hi { $user }"), ("a.c", "Bye")]),
+            catalog("es", &[("a.b", "Hola { $name }"), ("a.d", "Nada")]),
+        ];
+        let mut problems = Vec::new();
+        compare(&catalogs, &mut problems);
+
+        assert_eq!(problems, vec![
+            Problem::Placeholders {
+                language: "es".into(),
+                key: "a.b".into(),
+                missing: vec!["user".into()],
+                unknown: vec!["name".into()],
+            },
+            Problem::MissingKey("es".into(), "a.c".into()),
+            Problem::ExtraKey("es".into(), "a.d".into()),
+        ]);
+    }
+
+    #[test]
+    fn missing_reference() {
+        let mut problems = Vec::new();
+        compare(&[catalog("es", &[])], &mut problems);
+        assert_eq!(problems, vec![Problem::MissingReference("en".into())]);
+    }
+}
@@ -0,0 +1,112 @@
+//! Check the translations of the bot: every `i18n/*.json` file must have the
+//! keys of the English one with the same variables, and the keys used in
+//! `src/` must exist and be used.
+//!
+//! Run it with `cargo run -p i18n-check`, optionally passing the root of the
+//! repository. It exits with an error when any problem is found.
+
+use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
+use std::process;
+
+mod catalog;
+mod placeholders;
+mod source;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    Unreadable(String, String),
+    MissingReference(String),
+    MissingKey(String, String),
+    ExtraKey(String, String),
+    Placeholders {
+        language: String,
+        key: String,
+        missing: Vec<String>,
+        unknown: Vec<String>,
+    },
+    UnknownKey(String, Vec<String>),
+    UnusedKey(String),
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Unreadable(path, err) => write!(f, "Failed to read {}: {}", path, err),
+            Problem::MissingReference(language) => {
+                write!(f, "Missing the reference translations {}.json", language)
+            },
+            Problem::MissingKey(language, key) => write!(f, "{}: missing key '{}'", language, key),
+            Problem::ExtraKey(language, key) => write!(f, "{}: unknown key '{}'", language, key),
+            Problem::Placeholders {
+                language,
+                key,
+                missing,
+                unknown,
+            } => {
+                write!(f, "{}: variables of '{}' don't match", language, key)?;
+                if !missing.is_empty() {
+                    write!(f, ", missing ${}", missing.join(", $"))?;
+                }
+                if !unknown.is_empty() {
+                    write!(f, ", unknown ${}", unknown.join(", $"))?;
+                }
+                Ok(())
+            },
+            Problem::UnknownKey(key, locations) => {
+                write!(
+                    f,
+                    "Key '{}' is not translated, used in {}",
+                    key,
+                    locations.join(", ")
+                )
+            },
+            Problem::UnusedKey(key) => write!(f, "Key '{}' is never used", key),
+        }
+    }
+}
+
+/// Every problem with the translations of the repository at `root`.
+fn check(root: &Path) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    let catalogs = catalog::load(&root.join("i18n"), &mut problems);
+    catalog::compare(&catalogs, &mut problems);
+
+    let usage = source::scan(&root.join("src"), &mut problems);
+    if let Some(reference) = catalogs.iter().find(|c| c.language == catalog::REFERENCE) {
+        source::compare(&usage, reference, &mut problems);
+    }
+    problems
+}
+
+fn main() {
+    let root = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("../.."));
+
+    let problems = check(&root);
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    if !problems.is_empty() {
+        eprintln!("Found {} problems with the translations", problems.len());
+        process::exit(1);
+    }
+    println!("Translations are consistent");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::check;
+
+    #[test]
+    fn repository_translations() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let problems: Vec<String> = check(&root).iter().map(ToString::to_string).collect();
+        assert!(problems.is_empty(), "{}", problems.join("\n"));
+    }
+}
@@ -0,0 +1,35 @@
+use std::collections::BTreeSet;
+
+use regex::Regex;
+
+/// Variables open a placeable, `{ $name }` or `{ $count -> ... }`, or are the
+/// argument of a function, `{ DURATION($secs) }`.
+const VARIABLE: &str = r"[{(]\s*\$([A-Za-z0-9_]+)";
+
+/// Names of the variables used by a message.
+pub fn variables(message: &str) -> BTreeSet<String> {
+    let regex = Regex::new(VARIABLE).expect("Failed to compile variable regex");
+    regex
+        .captures_iter(message)
+        .map(|captures| captures[1].to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::variables;
+
+    #[test]
+    fn plain_and_select() {
+        let message = "Added { $count -> [one] a song *[other] { $count } songs } for { $user }";
+        let names: Vec<_> = variables(message).into_iter().collect();
+        assert_eq!(names, vec!["count", "user"]);
+    }
+
+    #[test]
+    fn functions() {
+        let message = "At most { DURATION($duration) }, costs $5";
+        let names: Vec<_> = variables(message).into_iter().collect();
+        assert_eq!(names, vec!["duration"]);
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+use walkdir::WalkDir;
+
+use crate::catalog::Catalog;
+use crate::Problem;
+
+/// Functions and methods that take translation keys as arguments.
+const CALLS: &str = r"(?:\.get|\.get_option|\.translate|get_default|send_info|send_translated_info|LocalizedError::new|LocalizedError::with)\(";
+
+/// String literals that look like translation keys, `"queue.added"`.
+const KEY: &str = r#""([a-z][a-z0-9_]*(?:\.[a-z0-9_]+)+)""#;
+
+/// Keys built at runtime, like `format!("settings.{}", name)`, up to the
+/// first argument.
+const PREFIX: &str = r#"format!\(\s*"([a-z][a-z0-9_]*(?:\.[a-z0-9_]+)*\.)\{\}"#;
+
+/// Translation keys found in the source code.
+#[derive(Debug, Default)]
+pub struct Usage {
+    /// Keys passed to translation functions, with the places they are used.
+    pub keys: BTreeMap<String, Vec<String>>,
+    /// Every string literal that looks like a key, wherever it is used.
+    pub literals: HashSet<String>,
+    /// Prefixes of keys built at runtime.
+    pub prefixes: Vec<String>,
+}
+
+struct Patterns {
+    calls: Regex,
+    key: Regex,
+    prefix: Regex,
+}
+
+impl Patterns {
+    fn new() -> Self {
+        Self {
+            calls: Regex::new(CALLS).expect("Failed to compile calls regex"),
+            key: Regex::new(KEY).expect("Failed to compile key regex"),
+            prefix: Regex::new(PREFIX).expect("Failed to compile prefix regex"),
+        }
+    }
+}
+
+/// Find the translation keys used by every Rust file in `dir`.
+pub fn scan(dir: &Path, problems: &mut Vec<Problem>) -> Usage {
+    let patterns = Patterns::new();
+    let mut usage = Usage::default();
+
+    let files = WalkDir::new(dir)
+        .sort_by(|a, b| a.file_name().cmp(b.file_name()))
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().and_then(|ext| ext.to_str()) == Some("rs"));
+    for entry in files {
+        let path = entry.path();
+        match fs::read_to_string(path) {
+            Ok(text) => scan_file(&patterns, &path.display().to_string(), &text, &mut usage),
+            Err(err) => problems.push(Problem::Unreadable(
+                path.display().to_string(),
+                err.to_string(),
+            )),
+        }
+    }
+    usage
+}
+
+fn scan_file(patterns: &Patterns, file: &str, text: &str, usage: &mut Usage) {
+    for call in patterns.calls.find_iter(text) {
+        let line = text[..call.start()].matches('\n').count() + 1;
+        let arguments = arguments(&text[call.end()..]);
+        for captures in patterns.key.captures_iter(arguments) {
+            usage
+                .keys
+                .entry(captures[1].to_string())
+                .or_default()
+                .push(format!("{}:{}", file, line));
+        }
+    }
+
+    for captures in patterns.key.captures_iter(text) {
+        usage.literals.insert(captures[1].to_string());
+    }
+    for captures in patterns.prefix.captures_iter(text) {
+        usage.prefixes.push(captures[1].to_string());
+    }
+}
+
+/// Arguments of a call, up to its closing parenthesis. Parentheses inside
+/// string literals are skipped.
+fn arguments(text: &str) -> &str {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string && depth == 0 => return &text[..i],
+            ')' if !in_string => depth -= 1,
+            _ => {},
+        }
+    }
+    text
+}
+
+/// Report keys used by the code that are not translated, and translated keys
+/// the code never uses.
+pub fn compare(usage: &Usage, reference: &Catalog, problems: &mut Vec<Problem>) {
+    for (key, locations) in &usage.keys {
+        if !reference.messages.contains_key(key) {
+            problems.push(Problem::UnknownKey(key.clone(), locations.clone()));
+        }
+    }
+
+    for key in reference.messages.keys() {
+        let dynamic = usage.prefixes.iter().any(|prefix| key.starts_with(prefix));
+        if !dynamic && !usage.literals.contains(key) {
+            problems.push(Problem::UnusedKey(key.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{arguments, scan_file, Patterns, Usage};
+
+    #[test]
+    fn call_arguments() {
+        assert_eq!(
+            arguments(r#""a.b", f(x), ")")) + 1"#),
+            r#""a.b", f(x), ")""#
+        );
+    }
+
+    #[test]
+    fn keys_in_calls() {
+        let text = r#"
+            let title = lang.get("queue.title");
+            send_info("voice.update", "voice.left", invocation, ctx).await?;
+            let err = LocalizedError::with("error.invalid", json!({ "value": value }));
+            let label = format!("settings.{}", name);
+            let part = format!("help.command.{}.{}", name, part);
+            let unit = ("duration.days", 86_400);
+            map.get("plain");
+        "#;
+        let mut usage = Usage::default();
+        scan_file(&Patterns::new(), "test.rs", text, &mut usage);
+
+        let keys: Vec<_> = usage.keys.keys().map(String::as_str).collect();
+        assert_eq!(keys, vec![
+            "error.invalid",
+            "queue.title",
+            "voice.left",
+            "voice.update"
+        ]);
+        assert_eq!(usage.keys["queue.title"], vec!["test.rs:2"]);
+        assert!(usage.literals.contains("duration.days"));
+        assert_eq!(usage.prefixes, vec!["settings.", "help.command."]);
+    }
+}
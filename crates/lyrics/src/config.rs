@@ -0,0 +1,102 @@
+use std::env;
+use std::str::FromStr;
+
+use crate::LyricsError;
+
+/// Comma separated list of providers, tried in the given order.
+const PROVIDERS_VAR: &str = "LYRICS_PROVIDERS";
+const GENIUS_TOKEN_VAR: &str = "GENIUS_ACCESS_TOKEN";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProviderKind {
+    Lrclib,
+    Genius,
+    LyricsOvh,
+}
+
+impl FromStr for ProviderKind {
+    type Err = LyricsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "lrclib" => Ok(ProviderKind::Lrclib),
+            "genius" => Ok(ProviderKind::Genius),
+            "lyricsovh" | "lyrics.ovh" => Ok(ProviderKind::LyricsOvh),
+            other => Err(LyricsError::UnknownProvider(other.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub providers: Vec<ProviderKind>,
+    pub genius_token: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            providers: vec![ProviderKind::Lrclib, ProviderKind::LyricsOvh],
+            genius_token: None,
+        }
+    }
+}
+
+impl Config {
+    /// Load the provider order from `LYRICS_PROVIDERS` and credentials from
+    /// their own variables. Genius is enabled by default only when its token
+    /// is set.
+    pub fn from_env() -> Result<Self, LyricsError> {
+        let genius_token = env::var(GENIUS_TOKEN_VAR)
+            .ok()
+            .filter(|token| !token.is_empty());
+
+        let providers = match env::var(PROVIDERS_VAR) {
+            Ok(list) if !list.trim().is_empty() => parse_providers(&list)?,
+            _ => {
+                let mut providers = Config::default().providers;
+                if genius_token.is_some() {
+                    providers.insert(1, ProviderKind::Genius);
+                }
+                providers
+            },
+        };
+
+        Ok(Self {
+            providers,
+            genius_token,
+        })
+    }
+}
+
+fn parse_providers(list: &str) -> Result<Vec<ProviderKind>, LyricsError> {
+    let mut providers = Vec::new();
+    for kind in list.split(',').filter(|s| !s.trim().is_empty()) {
+        let kind = kind.parse()?;
+        if !providers.contains(&kind) {
+            providers.push(kind);
+        }
+    }
+    Ok(providers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_providers, ProviderKind};
+
+    #[test]
+    fn provider_order() {
+        let providers =
+            parse_providers("genius, LRCLIB,lyrics.ovh,genius,").expect("Failed to parse");
+        assert_eq!(providers, vec![
+            ProviderKind::Genius,
+            ProviderKind::Lrclib,
+            ProviderKind::LyricsOvh
+        ]);
+    }
+
+    #[test]
+    fn unknown_provider() {
+        assert!(parse_providers("lrclib,azlyrics").is_err());
+    }
+}
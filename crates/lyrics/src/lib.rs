@@ -1,32 +1,135 @@
-use std::error::Error;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
-use tokio::process::Command;
+use thiserror::Error;
+
+/// Maximum length difference between a track and the lyrics found for it.
+pub(crate) const DURATION_TOLERANCE: Duration = Duration::from_secs(15);
+
+mod config;
+mod lrc;
+pub mod providers;
+mod query;
+
+pub use self::config::{Config, ProviderKind};
+pub use self::lrc::{SyncedLine, SyncedLyrics};
+pub use self::providers::Provider;
+pub use self::query::{normalize_title, Query};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SearchResult {
     pub title: String,
+    pub artist: String,
     pub lyrics: String,
+    pub provider: String,
+    pub source_url: Option<String>,
+    pub synced: Option<SyncedLyrics>,
+    pub duration: Option<Duration>,
+}
+
+#[derive(Debug, Error)]
+pub enum LyricsError {
+    #[error("No lyrics found for the requested song")]
+    NotFound,
+
+    #[error("No lyrics providers are configured")]
+    NoProviders,
+
+    #[error("Unknown lyrics provider '{0}'")]
+    UnknownProvider(String),
+
+    #[error("Missing credential {0} for lyrics provider")]
+    MissingCredential(&'static str),
+
+    #[error("Failed to parse provider response")]
+    Parse,
+
+    #[error("HTTP error")]
+    Http(#[from] reqwest::Error),
 }
 
-pub async fn search<S: AsRef<str>>(query: S) -> Result<SearchResult, Box<dyn Error>> {
-    let query = query.as_ref();
-    let output = Command::new("python3")
-        .arg("-c")
-        .arg(include_str!("lyrics.py"))
-        .args(query.split(' '))
-        .output()
-        .await?;
-    let output = String::from_utf8(output.stdout)?;
-
-    Ok(serde_json::from_str(&output)?)
+/// Lyrics client that tries every configured provider in order until one of
+/// them finds the requested song.
+pub struct Lyrics {
+    providers: Vec<Box<dyn Provider>>,
+}
+
+impl Lyrics {
+    pub fn new(config: &Config) -> Result<Self, LyricsError> {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("dreamer-lyrics/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        let providers = config
+            .providers
+            .iter()
+            .map(|kind| providers::create(*kind, config, client.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if providers.is_empty() {
+            return Err(LyricsError::NoProviders);
+        }
+        Ok(Self { providers })
+    }
+
+    pub fn from_env() -> Result<Self, LyricsError> {
+        Self::new(&Config::from_env()?)
+    }
+
+    pub fn providers(&self) -> impl Iterator<Item = &str> {
+        self.providers.iter().map(|provider| provider.name())
+    }
+
+    pub async fn search<Q: Into<Query>>(&self, query: Q) -> Result<SearchResult, LyricsError> {
+        self.search_where(query.into(), |_| true).await
+    }
+
+    /// Like [`Lyrics::search`], but only accepts results with time-synced
+    /// lyrics.
+    pub async fn search_synced<Q: Into<Query>>(
+        &self, query: Q,
+    ) -> Result<SearchResult, LyricsError> {
+        self.search_where(query.into(), |result| result.synced.is_some())
+            .await
+    }
+
+    async fn search_where<F>(&self, query: Query, accept: F) -> Result<SearchResult, LyricsError>
+    where
+        F: Fn(&SearchResult) -> bool, {
+        if query.title.trim().is_empty() {
+            return Err(LyricsError::NotFound);
+        }
+
+        for provider in &self.providers {
+            match provider.search(&query).await {
+                Ok(Some(result))
+                    if accept(&result)
+                        && query.matches_duration(result.duration, DURATION_TOLERANCE) =>
+                {
+                    return Ok(result)
+                },
+                Ok(_) => continue,
+                Err(err) => {
+                    tracing::warn!("Lyrics provider {} failed: {:?}", provider.name(), err);
+                },
+            }
+        }
+        Err(LyricsError::NotFound)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{Config, Lyrics, ProviderKind};
+
     #[tokio::test]
     async fn search() {
-        super::search("Wake me Up Avicii")
+        let config = Config {
+            providers: vec![ProviderKind::Lrclib, ProviderKind::LyricsOvh],
+            ..Config::default()
+        };
+        Lyrics::new(&config)
+            .expect("Failed to create client")
+            .search("Wake me Up Avicii")
             .await
             .expect("Failed to search");
     }
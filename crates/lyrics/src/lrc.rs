@@ -0,0 +1,164 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedLine {
+    pub time: Duration,
+    pub text: String,
+}
+
+/// Time-synced lyrics, parsed from the LRC format.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedLyrics {
+    lines: Vec<SyncedLine>,
+}
+
+impl SyncedLyrics {
+    /// Parse LRC text. Lines may carry several `[mm:ss.xx]` timestamps, and the
+    /// `[offset:ms]` tag shifts every line. Returns `None` when no timed line
+    /// is found.
+    pub fn parse<S: AsRef<str>>(lrc: S) -> Option<Self> {
+        let mut offset = 0i64;
+        let mut lines = Vec::new();
+
+        for line in lrc.as_ref().lines() {
+            let mut rest = line.trim();
+            let mut times = Vec::new();
+
+            while let Some(tag_end) = rest.strip_prefix('[').and_then(|tag| tag.find(']')) {
+                let tag = &rest[1..=tag_end];
+                if let Some(time) = parse_timestamp(tag) {
+                    times.push(time);
+                } else if let Some(value) = tag.strip_prefix("offset:") {
+                    offset = value.trim().parse().unwrap_or(0);
+                }
+                rest = rest[tag_end + 2..].trim_start();
+            }
+
+            for time in times {
+                lines.push(SyncedLine {
+                    time,
+                    text: rest.to_string(),
+                });
+            }
+        }
+
+        if lines.is_empty() {
+            return None;
+        }
+
+        // A positive offset makes lyrics appear sooner
+        for line in &mut lines {
+            line.time = if offset >= 0 {
+                line.time
+                    .checked_sub(Duration::from_millis(offset as u64))
+                    .unwrap_or_default()
+            } else {
+                line.time + Duration::from_millis(offset.unsigned_abs())
+            };
+        }
+        lines.sort_by_key(|line| line.time);
+        Some(Self { lines })
+    }
+
+    pub fn lines(&self) -> &[SyncedLine] {
+        &self.lines
+    }
+
+    /// Index of the line being sung at `position`, or `None` before the first
+    /// line starts.
+    pub fn index_at(&self, position: Duration) -> Option<usize> {
+        let next = self.lines.partition_point(|line| line.time <= position);
+        next.checked_sub(1)
+    }
+
+    /// Plain text of the lyrics, without timestamps.
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Parse a `mm:ss`, `mm:ss.xx` or `mm:ss.xxx` timestamp.
+fn parse_timestamp(tag: &str) -> Option<Duration> {
+    let (minutes, seconds) = tag.split_once(':')?;
+    let minutes: u64 = minutes.trim().parse().ok()?;
+    let (seconds, fraction) = match seconds.split_once('.') {
+        Some((seconds, fraction)) => (seconds, fraction),
+        None => (seconds, "0"),
+    };
+    let seconds: u64 = seconds.trim().parse().ok()?;
+    if seconds >= 60 || fraction.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let fraction: String = fraction.chars().chain("000".chars()).take(3).collect();
+    let millis: u64 = fraction.parse().ok()?;
+
+    Some(Duration::from_millis(
+        (minutes * 60 + seconds) * 1000 + millis,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{parse_timestamp, SyncedLyrics};
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(
+            parse_timestamp("00:12.34"),
+            Some(Duration::from_millis(12_340))
+        );
+        assert_eq!(
+            parse_timestamp("01:02.005"),
+            Some(Duration::from_millis(62_005))
+        );
+        assert_eq!(parse_timestamp("03:00"), Some(Duration::from_secs(180)));
+        assert_eq!(parse_timestamp("ar:Avicii"), None);
+        assert_eq!(parse_timestamp("00:75.00"), None);
+    }
+
+    #[test]
+    fn parse_lrc() {
+        let lyrics = SyncedLyrics::parse(include_str!("../fixtures/wake_me_up.lrc"))
+            .expect("Failed to parse lyrics");
+        let lines: Vec<(u64, &str)> = lyrics
+            .lines()
+            .iter()
+            .map(|line| (line.time.as_millis() as u64, line.text.as_str()))
+            .collect();
+
+        assert_eq!(lines, vec![
+            (9_000, "Feeling my way through the darkness"),
+            (13_000, "Guided by a beating heart"),
+            (17_500, ""),
+            (40_000, "So wake me up when it's all over"),
+            (90_000, "So wake me up when it's all over"),
+        ]);
+    }
+
+    #[test]
+    fn plain_text_is_not_synced() {
+        assert_eq!(
+            SyncedLyrics::parse("Feeling my way\nthrough the darkness"),
+            None
+        );
+    }
+
+    #[test]
+    fn index_at_position() {
+        let lyrics = SyncedLyrics::parse("[00:10.00]one\n[00:20.00]two\n[00:30.00]three")
+            .expect("Failed to parse lyrics");
+
+        assert_eq!(lyrics.index_at(Duration::from_secs(5)), None);
+        assert_eq!(lyrics.index_at(Duration::from_secs(10)), Some(0));
+        assert_eq!(lyrics.index_at(Duration::from_secs(25)), Some(1));
+        assert_eq!(lyrics.index_at(Duration::from_secs(300)), Some(2));
+    }
+}
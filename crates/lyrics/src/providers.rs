@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use reqwest::Client;
+
+use crate::{Config, LyricsError, ProviderKind, Query, SearchResult};
+
+mod genius;
+mod lrclib;
+mod lyrics_ovh;
+
+pub use self::genius::Genius;
+pub use self::lrclib::Lrclib;
+pub use self::lyrics_ovh::LyricsOvh;
+
+/// A lyrics backend. Returns `Ok(None)` when the song is not known by the
+/// provider, so the next one can be tried. Providers should use the structured
+/// artist and title of the query when they support it, and report the length of
+/// the song they found so it can be checked against the query.
+#[async_trait]
+pub trait Provider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn search(&self, query: &Query) -> Result<Option<SearchResult>, LyricsError>;
+}
+
+pub(crate) fn create(
+    kind: ProviderKind, config: &Config, client: Client,
+) -> Result<Box<dyn Provider>, LyricsError> {
+    let provider: Box<dyn Provider> = match kind {
+        ProviderKind::Lrclib => Box::new(Lrclib::new(client)),
+        ProviderKind::LyricsOvh => Box::new(LyricsOvh::new(client)),
+        ProviderKind::Genius => {
+            let token = config
+                .genius_token
+                .clone()
+                .ok_or(LyricsError::MissingCredential("GENIUS_ACCESS_TOKEN"))?;
+            Box::new(Genius::new(client, token))
+        },
+    };
+    Ok(provider)
+}
+
+/// Normalize line endings and surrounding whitespace of fetched lyrics.
+fn clean_lyrics(lyrics: &str) -> String {
+    lyrics.replace("\r\n", "\n").trim().to_string()
+}
@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use scraper::{Html, Node, Selector};
+use serde::Deserialize;
+
+use super::{clean_lyrics, Provider};
+use crate::{LyricsError, Query, SearchResult};
+
+const SEARCH_URL: &str = "https://api.genius.com/search";
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    response: Hits,
+}
+
+#[derive(Debug, Deserialize)]
+struct Hits {
+    hits: Vec<Hit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Hit {
+    #[serde(rename = "type")]
+    kind: String,
+    result: Song,
+}
+
+#[derive(Debug, Deserialize)]
+struct Song {
+    title: String,
+    url: String,
+    primary_artist: PrimaryArtist,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrimaryArtist {
+    name: String,
+}
+
+/// [Genius](https://genius.com). The API only returns song pages, so lyrics are
+/// scraped from the page markup.
+pub struct Genius {
+    client: Client,
+    token: String,
+}
+
+impl Genius {
+    pub fn new(client: Client, token: String) -> Self {
+        Self { client, token }
+    }
+}
+
+#[async_trait]
+impl Provider for Genius {
+    fn name(&self) -> &'static str {
+        "Genius"
+    }
+
+    async fn search(&self, query: &Query) -> Result<Option<SearchResult>, LyricsError> {
+        let search: SearchResponse = self
+            .client
+            .get(SEARCH_URL)
+            .bearer_auth(&self.token)
+            .query(&[("q", query.text())])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let song = match search
+            .response
+            .hits
+            .into_iter()
+            .find(|hit| hit.kind == "song")
+        {
+            Some(hit) => hit.result,
+            None => return Ok(None),
+        };
+
+        let page = self
+            .client
+            .get(&song.url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        let lyrics = extract_lyrics(&page);
+        if lyrics.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(SearchResult {
+            title: song.title,
+            artist: song.primary_artist.name,
+            lyrics,
+            provider: self.name().to_string(),
+            source_url: Some(song.url),
+            synced: None,
+            duration: None,
+        }))
+    }
+}
+
+fn extract_lyrics(page: &str) -> String {
+    let document = Html::parse_document(page);
+    let selector = match Selector::parse(r#"div[data-lyrics-container="true"]"#) {
+        Ok(selector) => selector,
+        Err(_) => return String::new(),
+    };
+
+    let mut lyrics = String::new();
+    for container in document.select(&selector) {
+        for node in container.descendants() {
+            match node.value() {
+                Node::Text(text) => lyrics.push_str(text),
+                Node::Element(element) if element.name() == "br" => lyrics.push('\n'),
+                _ => {},
+            }
+        }
+        lyrics.push('\n');
+    }
+    clean_lyrics(&lyrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::extract_lyrics;
+
+    #[test]
+    fn extract_page_lyrics() {
+        let page = include_str!("../../fixtures/genius.html");
+        assert_eq!(
+            extract_lyrics(page),
+            "[Verse 1]\nFeeling my way through the darkness\nGuided by a beating \
+             heart\n\n[Chorus]\nSo wake me up when it's all over"
+        );
+    }
+
+    #[test]
+    fn extract_missing_lyrics() {
+        assert_eq!(extract_lyrics("<html><body></body></html>"), "");
+    }
+}
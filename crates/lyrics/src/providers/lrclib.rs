@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+
+use super::{clean_lyrics, Provider};
+use crate::{LyricsError, Query, SearchResult, SyncedLyrics, DURATION_TOLERANCE};
+
+const SEARCH_URL: &str = "https://lrclib.net/api/search";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Track {
+    track_name: String,
+    artist_name: String,
+    duration: Option<f64>,
+    plain_lyrics: Option<String>,
+    synced_lyrics: Option<String>,
+}
+
+/// [LRCLIB](https://lrclib.net), an open lyrics database without credentials.
+pub struct Lrclib {
+    client: Client,
+}
+
+impl Lrclib {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Provider for Lrclib {
+    fn name(&self) -> &'static str {
+        "LRCLIB"
+    }
+
+    async fn search(&self, query: &Query) -> Result<Option<SearchResult>, LyricsError> {
+        let request = self.client.get(SEARCH_URL);
+        let request = match &query.artist {
+            Some(artist) => request.query(&[
+                ("track_name", query.title.as_str()),
+                ("artist_name", artist.as_str()),
+            ]),
+            None => request.query(&[("q", query.title.as_str())]),
+        };
+        let tracks: Vec<Track> = request.send().await?.error_for_status()?.json().await?;
+
+        let mut results: Vec<SearchResult> = tracks
+            .into_iter()
+            .filter_map(|track| {
+                let synced = track.synced_lyrics.as_deref().and_then(SyncedLyrics::parse);
+                let lyrics = track
+                    .plain_lyrics
+                    .filter(|lyrics| !lyrics.trim().is_empty())
+                    .map(|lyrics| clean_lyrics(&lyrics))
+                    .or_else(|| synced.as_ref().map(SyncedLyrics::text))?;
+                Some(SearchResult {
+                    title: track.track_name,
+                    artist: track.artist_name,
+                    lyrics,
+                    provider: self.name().to_string(),
+                    // LRCLIB has no pages for tracks, only API responses
+                    source_url: None,
+                    synced,
+                    duration: track
+                        .duration
+                        .filter(|secs| secs.is_finite() && *secs > 0.0)
+                        .map(Duration::from_secs_f64),
+                })
+            })
+            .collect();
+
+        // Prefer the most relevant result of the right length that can follow
+        // playback
+        results.sort_by_key(|result| {
+            (
+                !query.matches_duration(result.duration, DURATION_TOLERANCE),
+                result.synced.is_none(),
+            )
+        });
+        Ok(results.into_iter().next())
+    }
+}
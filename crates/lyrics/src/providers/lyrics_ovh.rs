@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode, Url};
+use serde::Deserialize;
+
+use super::{clean_lyrics, Provider};
+use crate::{LyricsError, Query, SearchResult, DURATION_TOLERANCE};
+
+const API_URL: &str = "https://api.lyrics.ovh";
+
+#[derive(Debug, Deserialize)]
+struct Suggestions {
+    data: Vec<Suggestion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Suggestion {
+    title: String,
+    link: Option<String>,
+    duration: Option<u64>,
+    artist: SuggestionArtist,
+}
+
+#[derive(Debug, Deserialize)]
+struct SuggestionArtist {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Lyrics {
+    lyrics: String,
+}
+
+/// [lyrics.ovh](https://lyrics.ovh), a free API that needs the artist and title
+/// separately. Queries without an artist are resolved through its suggestion
+/// endpoint first.
+pub struct LyricsOvh {
+    client: Client,
+}
+
+impl LyricsOvh {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    fn url(segments: &[&str]) -> Result<Url, LyricsError> {
+        let mut url = Url::parse(API_URL).map_err(|_| LyricsError::Parse)?;
+        url.path_segments_mut()
+            .map_err(|_| LyricsError::Parse)?
+            .extend(segments);
+        Ok(url)
+    }
+
+    async fn suggest(&self, query: &Query) -> Result<Option<Suggestion>, LyricsError> {
+        let suggestions: Suggestions = self
+            .client
+            .get(Self::url(&["suggest", &query.text()])?)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(suggestions.data.into_iter().find(|suggestion| {
+            let duration = suggestion.duration.map(Duration::from_secs);
+            query.matches_duration(duration, DURATION_TOLERANCE)
+        }))
+    }
+}
+
+#[async_trait]
+impl Provider for LyricsOvh {
+    fn name(&self) -> &'static str {
+        "lyrics.ovh"
+    }
+
+    async fn search(&self, query: &Query) -> Result<Option<SearchResult>, LyricsError> {
+        let suggestion = match &query.artist {
+            Some(artist) => Suggestion {
+                title: query.title.clone(),
+                link: None,
+                duration: None,
+                artist: SuggestionArtist {
+                    name: artist.clone(),
+                },
+            },
+            None => match self.suggest(query).await? {
+                Some(suggestion) => suggestion,
+                None => return Ok(None),
+            },
+        };
+
+        let response = self
+            .client
+            .get(Self::url(&[
+                "v1",
+                &suggestion.artist.name,
+                &suggestion.title,
+            ])?)
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let lyrics: Lyrics = response.error_for_status()?.json().await?;
+        let lyrics = strip_header(&clean_lyrics(&lyrics.lyrics));
+        if lyrics.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(SearchResult {
+            title: suggestion.title,
+            artist: suggestion.artist.name,
+            lyrics,
+            provider: self.name().to_string(),
+            source_url: suggestion.link,
+            synced: None,
+            duration: suggestion.duration.map(Duration::from_secs),
+        }))
+    }
+}
+
+/// lyrics.ovh prepends a "Paroles de la chanson ..." line to some results.
+fn strip_header(lyrics: &str) -> String {
+    match lyrics.split_once('\n') {
+        Some((first, rest)) if first.starts_with("Paroles de la chanson") => {
+            rest.trim().to_string()
+        },
+        _ => lyrics.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::strip_header;
+
+    #[test]
+    fn strip_lyrics_header() {
+        assert_eq!(
+            strip_header("Paroles de la chanson Wake Me Up par Avicii\nFeeling my way"),
+            "Feeling my way"
+        );
+        assert_eq!(strip_header("Feeling my way"), "Feeling my way");
+    }
+}
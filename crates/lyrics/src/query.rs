@@ -0,0 +1,281 @@
+use std::time::Duration;
+
+/// Bracketed parts of a video title containing any of these words are noise,
+/// not part of the song name.
+const NOISE_WORDS: &[&str] = &[
+    "official",
+    "video",
+    "audio",
+    "lyric",
+    "lyrics",
+    "letra",
+    "hd",
+    "hq",
+    "4k",
+    "remaster",
+    "remastered",
+    "visualizer",
+    "visualiser",
+    "mv",
+    "m/v",
+    "explicit",
+    "clean",
+    "color coded",
+];
+
+/// Suffixes YouTube appends to automatically generated artist channels.
+const CHANNEL_SUFFIXES: &[&str] = &[" - Topic", "VEVO", "Official"];
+
+/// A lyrics lookup. The title is always set, the artist and duration are used
+/// when the track metadata is known.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Query {
+    pub title: String,
+    pub artist: Option<String>,
+    pub duration: Option<Duration>,
+}
+
+impl Query {
+    pub fn new<S: Into<String>>(title: S) -> Self {
+        Self {
+            title: title.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn artist<S: Into<String>>(mut self, artist: S) -> Self {
+        let artist = artist.into();
+        self.artist = Some(artist).filter(|artist| !artist.trim().is_empty());
+        self
+    }
+
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Build a query from a video title, cleaning the usual "(Official Video)"
+    /// noise. When `artist` is unknown it is taken from an "Artist - Title"
+    /// title, or from an automatically generated `channel` name.
+    pub fn from_video<S: AsRef<str>>(
+        title: S, artist: Option<&str>, channel: Option<&str>,
+    ) -> Self {
+        let title = normalize_title(title.as_ref());
+
+        if let Some(artist) = artist.filter(|artist| !artist.trim().is_empty()) {
+            return Self::new(title).artist(artist.trim());
+        }
+        if let Some((artist, song)) = title.split_once(" - ") {
+            return Self::new(song.trim()).artist(artist.trim());
+        }
+        let channel = channel.and_then(|channel| {
+            CHANNEL_SUFFIXES
+                .iter()
+                .find_map(|suffix| channel.strip_suffix(suffix))
+        });
+        match channel {
+            Some(artist) => Self::new(title).artist(artist.trim()),
+            None => Self::new(title),
+        }
+    }
+
+    /// Free text form of the query, for providers without structured search.
+    pub fn text(&self) -> String {
+        match &self.artist {
+            Some(artist) => format!("{} {}", artist, self.title),
+            None => self.title.clone(),
+        }
+    }
+
+    /// Normalized `(artist, title)` pair identifying this song.
+    pub fn key(&self) -> (String, String) {
+        let normalize = |s: &str| {
+            s.to_lowercase()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        (
+            normalize(self.artist.as_deref().unwrap_or_default()),
+            normalize(&self.title),
+        )
+    }
+
+    /// Whether a result of length `duration` may be this song.
+    pub fn matches_duration(&self, duration: Option<Duration>, tolerance: Duration) -> bool {
+        match (self.duration, duration) {
+            (Some(expected), Some(found)) => expected.max(found) - expected.min(found) <= tolerance,
+            _ => true,
+        }
+    }
+}
+
+impl From<&str> for Query {
+    fn from(text: &str) -> Self {
+        Self::new(text.trim())
+    }
+}
+
+impl From<String> for Query {
+    fn from(text: String) -> Self {
+        Self::from(text.as_str())
+    }
+}
+
+impl From<&String> for Query {
+    fn from(text: &String) -> Self {
+        Self::from(text.as_str())
+    }
+}
+
+/// Strip bracketed noise like "(Official Video)" or "[HD]" and featured
+/// artists from a video title.
+pub fn normalize_title(title: &str) -> String {
+    let mut out = String::with_capacity(title.len());
+    let mut rest = title;
+
+    while let Some(start) = rest.find(&['(', '['][..]) {
+        let close = if rest[start..].starts_with('(') {
+            ')'
+        } else {
+            ']'
+        };
+        let end = match rest[start..].find(close) {
+            Some(end) => start + end,
+            None => break,
+        };
+        let inner = rest[start + 1..end].to_lowercase();
+        out.push_str(&rest[..start]);
+        if !is_noise(&inner) {
+            out.push_str(&rest[start..=end]);
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+
+    // Trailing "| Official Audio" style segments
+    let out = out
+        .split(" | ")
+        .enumerate()
+        .filter(|(i, segment)| *i == 0 || !is_noise(&segment.to_lowercase()))
+        .map(|(_, segment)| segment)
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    // Featured artists confuse providers
+    let feat = [" feat. ", " ft. ", " feat ", " ft "]
+        .iter()
+        .filter_map(|sep| find_ignore_ascii_case(&out, sep))
+        .min();
+    let out = match feat {
+        Some(feat) => &out[..feat],
+        None => &out[..],
+    };
+
+    out.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c| c == '-' || c == '|' || c == ' ')
+        .to_string()
+}
+
+/// Byte offset of an ASCII `needle` in `haystack`, ignoring ASCII case.
+/// Unlike searching a lowercased copy, the offset is always valid in
+/// `haystack`, since lowercasing may change the length of other characters.
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+fn is_noise(inner: &str) -> bool {
+    inner
+        .split(|c: char| !c.is_alphanumeric() && c != '/')
+        .any(|word| NOISE_WORDS.contains(&word))
+        || NOISE_WORDS
+            .iter()
+            .filter(|word| word.contains(' '))
+            .any(|word| inner.contains(word))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{normalize_title, Query};
+
+    #[test]
+    fn strip_title_noise() {
+        assert_eq!(
+            normalize_title("Avicii - Wake Me Up (Official Video)"),
+            "Avicii - Wake Me Up"
+        );
+        assert_eq!(
+            normalize_title("Daft Punk - One More Time [HD] (Lyrics)"),
+            "Daft Punk - One More Time"
+        );
+        assert_eq!(
+            normalize_title("Calvin Harris - Summer ft. Someone (Audio)"),
+            "Calvin Harris - Summer"
+        );
+        assert_eq!(
+            normalize_title("Sweet Child O' Mine (Remastered 2004) | Official Audio"),
+            "Sweet Child O' Mine"
+        );
+        assert_eq!(normalize_title("Yesterday (Live)"), "Yesterday (Live)");
+    }
+
+    #[test]
+    fn strip_featured_artists() {
+        assert_eq!(
+            normalize_title("Calvin Harris - Summer FT. Someone"),
+            "Calvin Harris - Summer"
+        );
+        assert_eq!(
+            normalize_title("İSTANBUL İÇİN - Şarkı feat. Birisi"),
+            "İSTANBUL İÇİN - Şarkı"
+        );
+        assert_eq!(normalize_title("Ölüdeniz Feat Ünlü"), "Ölüdeniz");
+    }
+
+    #[test]
+    fn query_from_video() {
+        assert_eq!(
+            Query::from_video(
+                "Avicii - Wake Me Up (Official Video)",
+                None,
+                Some("AviciiVEVO")
+            ),
+            Query::new("Wake Me Up").artist("Avicii")
+        );
+        assert_eq!(
+            Query::from_video("Wake Me Up", None, Some("Avicii - Topic")),
+            Query::new("Wake Me Up").artist("Avicii")
+        );
+        assert_eq!(
+            Query::from_video("Wake Me Up (Lyric Video)", Some("Avicii"), None),
+            Query::new("Wake Me Up").artist("Avicii")
+        );
+        assert_eq!(
+            Query::from_video("Wake Me Up", None, Some("Some Uploader")),
+            Query::new("Wake Me Up")
+        );
+    }
+
+    #[test]
+    fn query_key() {
+        let query = Query::new("Wake  Me Up").artist("AVICII");
+        assert_eq!(query.key(), ("avicii".into(), "wake me up".into()));
+    }
+
+    #[test]
+    fn duration_match() {
+        let query = Query::new("Wake Me Up").duration(Duration::from_secs(247));
+        let tolerance = Duration::from_secs(10);
+
+        assert!(query.matches_duration(Some(Duration::from_secs(250)), tolerance));
+        assert!(!query.matches_duration(Some(Duration::from_secs(300)), tolerance));
+        assert!(query.matches_duration(None, tolerance));
+    }
+}
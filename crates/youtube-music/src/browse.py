@@ -0,0 +1,58 @@
+#!/usr/bin/python
+
+import sys
+import json
+
+from ytmusicapi import YTMusic
+
+if len(sys.argv) < 4:
+    print("{}")
+    exit()
+
+kind = sys.argv[1]
+browse_id = sys.argv[2]
+limit = int(sys.argv[3])
+
+ytmusic = YTMusic()
+
+if kind == "album":
+    album = ytmusic.get_album(browse_id)
+    tracks = album.get("tracks", [])
+    for track in tracks:
+        track["album"] = {"name": album.get("title"), "id": browse_id}
+    result = {
+        "title": album.get("title"),
+        "description": album.get("description"),
+        "thumbnails": album.get("thumbnails"),
+        "artists": album.get("artists"),
+        "year": album.get("year"),
+        "tracks": tracks[:limit],
+    }
+elif kind == "artist":
+    artist = ytmusic.get_artist(browse_id)
+    songs = artist.get("songs") or {}
+    tracks = songs.get("results", [])
+    if songs.get("browseId"):
+        tracks = ytmusic.get_playlist(songs["browseId"], limit=limit).get("tracks", tracks)
+    result = {
+        "title": artist.get("name"),
+        "description": artist.get("description"),
+        "thumbnails": artist.get("thumbnails"),
+        "artists": [{"name": artist.get("name"), "id": artist.get("channelId")}],
+        "tracks": tracks[:limit],
+    }
+elif kind == "playlist":
+    playlist = ytmusic.get_playlist(browse_id, limit=limit)
+    author = playlist.get("author")
+    result = {
+        "title": playlist.get("title"),
+        "description": playlist.get("description"),
+        "thumbnails": playlist.get("thumbnails"),
+        "artists": [author] if author else None,
+        "year": playlist.get("year"),
+        "tracks": playlist.get("tracks", [])[:limit],
+    }
+else:
+    result = {}
+
+print(json.dumps(result))
@@ -1,13 +1,20 @@
 use std::error::Error;
+use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 use tokio::process::Command;
 
+mod suggestions;
+
+pub use self::suggestions::{suggestions, SuggestionCache};
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchResult {
+    #[serde(default, alias = "artist")]
     pub title: String,
-    pub video_id: String,
+    pub video_id: Option<String>,
+    pub browse_id: Option<String>,
     pub result_type: Option<String>,
     pub duration: Option<String>,
     pub thumbnails: Option<Vec<Thumbnail>>,
@@ -21,14 +28,14 @@ pub struct SearchResult {
 #[serde(rename_all = "camelCase")]
 pub struct Album {
     pub name: String,
-    pub id: String,
+    pub id: Option<String>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Artist {
     pub name: String,
-    pub id: String,
+    pub id: Option<String>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -39,10 +46,77 @@ pub struct Thumbnail {
     pub height: Option<i64>,
 }
 
-pub async fn search<S: AsRef<str>>(query: S) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+/// A browsable collection of tracks: an album, an artist's top songs or a
+/// playlist.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Collection {
+    #[serde(default)]
+    pub title: String,
+    pub description: Option<String>,
+    pub thumbnails: Option<Vec<Thumbnail>>,
+    pub artists: Option<Vec<Artist>>,
+    pub year: Option<String>,
+    #[serde(default)]
+    pub tracks: Vec<SearchResult>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SearchFilter {
+    Songs,
+    Videos,
+    Albums,
+    Artists,
+    Playlists,
+    CommunityPlaylists,
+}
+
+impl From<SearchFilter> for &'static str {
+    fn from(filter: SearchFilter) -> &'static str {
+        match filter {
+            SearchFilter::Songs => "songs",
+            SearchFilter::Videos => "videos",
+            SearchFilter::Albums => "albums",
+            SearchFilter::Artists => "artists",
+            SearchFilter::Playlists => "playlists",
+            SearchFilter::CommunityPlaylists => "community_playlists",
+        }
+    }
+}
+
+impl Display for SearchFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string: &str = (*self).into();
+        write!(f, "{}", string)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum BrowseKind {
+    Album,
+    Artist,
+    Playlist,
+}
+
+impl From<BrowseKind> for &'static str {
+    fn from(kind: BrowseKind) -> &'static str {
+        match kind {
+            BrowseKind::Album => "album",
+            BrowseKind::Artist => "artist",
+            BrowseKind::Playlist => "playlist",
+        }
+    }
+}
+
+pub async fn search<S: AsRef<str>>(
+    query: S, filter: SearchFilter, limit: usize,
+) -> Result<Vec<SearchResult>, Box<dyn Error>> {
+    let filter: &str = filter.into();
     let output = Command::new("python3")
         .arg("-c")
         .arg(include_str!("search.py"))
+        .arg(filter)
+        .arg(limit.to_string())
         .args(query.as_ref().split(' '))
         .output()
         .await?;
@@ -52,12 +126,65 @@ pub async fn search<S: AsRef<str>>(query: S) -> Result<Vec<SearchResult>, Box<dy
     Ok(list)
 }
 
+/// List the tracks of an album, given its browse id (`MPREb_...`).
+pub async fn album<S: AsRef<str>>(browse_id: S) -> Result<Collection, Box<dyn Error>> {
+    browse(BrowseKind::Album, browse_id, usize::MAX).await
+}
+
+/// List the top songs of an artist, given its channel id (`UC...`).
+pub async fn artist<S: AsRef<str>>(
+    channel_id: S, limit: usize,
+) -> Result<Collection, Box<dyn Error>> {
+    browse(BrowseKind::Artist, channel_id, limit).await
+}
+
+/// List the items of a playlist, given its playlist id.
+pub async fn playlist<S: AsRef<str>>(
+    playlist_id: S, limit: usize,
+) -> Result<Collection, Box<dyn Error>> {
+    browse(BrowseKind::Playlist, playlist_id, limit).await
+}
+
+async fn browse<S: AsRef<str>>(
+    kind: BrowseKind, id: S, limit: usize,
+) -> Result<Collection, Box<dyn Error>> {
+    let kind: &str = kind.into();
+    let output = Command::new("python3")
+        .arg("-c")
+        .arg(include_str!("browse.py"))
+        .arg(kind)
+        .arg(id.as_ref())
+        .arg(limit.min(i32::MAX as usize).to_string())
+        .output()
+        .await?;
+    let mut output = String::from_utf8(output.stdout)?;
+
+    let collection = simd_json::serde::from_str(&mut output)?;
+    Ok(collection)
+}
+
 #[cfg(test)]
 mod tests {
+    use super::SearchFilter;
+
     #[tokio::test]
     async fn search() {
-        super::search("Wake me Up Avicii")
+        super::search("Wake me Up Avicii", SearchFilter::Songs, 1)
             .await
             .expect("Failed to search");
     }
+
+    #[tokio::test]
+    async fn album() {
+        let albums = super::search("True Avicii", SearchFilter::Albums, 1)
+            .await
+            .expect("Failed to search");
+        let browse_id = albums
+            .first()
+            .and_then(|album| album.browse_id.clone())
+            .expect("No album found");
+
+        let album = super::album(browse_id).await.expect("Failed to browse");
+        assert!(!album.tracks.is_empty());
+    }
 }
@@ -5,13 +5,19 @@ import json
 
 from ytmusicapi import YTMusic
 
-arguments = " ".join(sys.argv[1:])
+if len(sys.argv) < 4:
+    print("[]")
+    exit()
+
+search_filter = sys.argv[1]
+limit = int(sys.argv[2])
+arguments = " ".join(sys.argv[3:])
 
 if len(arguments) == 0:
     print("[]")
     exit()
 
 ytmusic = YTMusic()
-search_results = ytmusic.search(query=arguments, filter="songs", limit=1)
+search_results = ytmusic.search(query=arguments, filter=search_filter, limit=limit)
 
-print(json.dumps(search_results))
+print(json.dumps(search_results[:limit]))
@@ -0,0 +1,17 @@
+#!/usr/bin/python
+
+import sys
+import json
+
+from ytmusicapi import YTMusic
+
+arguments = " ".join(sys.argv[1:])
+
+if len(arguments) == 0:
+    print("[]")
+    exit()
+
+ytmusic = YTMusic()
+suggestions = ytmusic.get_search_suggestions(arguments)
+
+print(json.dumps(suggestions))
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use tokio::process::Command;
+use tokio::sync::Mutex;
+
+/// Maximum amount of prefixes kept by a [`SuggestionCache`].
+const MAX_CACHED_PREFIXES: usize = 1024;
+
+/// Fetch YouTube Music query completions for a partial search string.
+pub async fn suggestions<S: AsRef<str>>(query: S) -> Result<Vec<String>, Box<dyn Error>> {
+    let output = Command::new("python3")
+        .arg("-c")
+        .arg(include_str!("suggestions.py"))
+        .args(query.as_ref().split(' '))
+        .output()
+        .await?;
+    let output = String::from_utf8(output.stdout)?;
+
+    parse(&output)
+}
+
+fn parse(output: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let list: Vec<String> = serde_json::from_str(output)?;
+    Ok(list.into_iter().filter(|s| !s.is_empty()).collect())
+}
+
+fn normalize(prefix: &str) -> String {
+    prefix.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Short-lived cache of query completions, keyed by normalized prefix.
+///
+/// Autocomplete requests arrive on every keystroke, so repeated prefixes are
+/// served from memory until `ttl` expires.
+#[derive(Debug)]
+pub struct SuggestionCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, Vec<String>)>>,
+}
+
+impl SuggestionCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get completions for `prefix`, querying YouTube Music on a cache miss.
+    pub async fn get<S: AsRef<str>>(&self, prefix: S) -> Result<Vec<String>, Box<dyn Error>> {
+        let key = normalize(prefix.as_ref());
+        if key.is_empty() {
+            return Ok(Vec::new());
+        }
+        if let Some(cached) = self.cached(&key).await {
+            return Ok(cached);
+        }
+
+        let fetched = suggestions(&key).await?;
+        self.insert(key, fetched.clone()).await;
+        Ok(fetched)
+    }
+
+    async fn cached(&self, key: &str) -> Option<Vec<String>> {
+        let entries = self.entries.lock().await;
+        let (inserted, list) = entries.get(key)?;
+        if inserted.elapsed() > self.ttl {
+            return None;
+        }
+        Some(list.clone())
+    }
+
+    async fn insert(&self, key: String, list: Vec<String>) {
+        let mut entries = self.entries.lock().await;
+        let ttl = self.ttl;
+        entries.retain(|_, (inserted, _)| inserted.elapsed() <= ttl);
+        if entries.len() >= MAX_CACHED_PREFIXES {
+            entries.clear();
+        }
+        entries.insert(key, (Instant::now(), list));
+    }
+}
+
+impl Default for SuggestionCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(60))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{normalize, parse, SuggestionCache};
+
+    #[test]
+    fn parse_suggestions() {
+        let output = include_str!("../fixtures/suggestions.json");
+        let list = parse(output).expect("Failed to parse suggestions");
+        assert_eq!(list, vec![
+            "avicii wake me up",
+            "avicii wake me up lyrics",
+            "avicii waiting for love",
+            "avicii wake me up acoustic",
+        ]);
+    }
+
+    #[test]
+    fn parse_empty_suggestions() {
+        let output = include_str!("../fixtures/suggestions_empty.json");
+        let list = parse(output).expect("Failed to parse suggestions");
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn parse_invalid_output() {
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn normalize_prefix() {
+        assert_eq!(normalize("  Avicii   Wake "), "avicii wake");
+    }
+
+    #[tokio::test]
+    async fn cache_expires() {
+        let cache = SuggestionCache::new(Duration::from_millis(50));
+        cache
+            .insert("avicii".into(), vec!["avicii wake me up".into()])
+            .await;
+        assert_eq!(
+            cache.cached("avicii").await,
+            Some(vec!["avicii wake me up".to_string()])
+        );
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(cache.cached("avicii").await, None);
+    }
+
+    #[tokio::test]
+    async fn empty_prefix_skips_lookup() {
+        let cache = SuggestionCache::default();
+        let list = cache.get("   ").await.expect("Failed to get suggestions");
+        assert!(list.is_empty());
+    }
+}
@@ -1,3 +1,6 @@
+pub mod dashboard;
+pub mod player;
 pub mod queue;
 pub mod source;
 pub mod spotify;
+pub mod ytmusic;
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::lazy::SyncLazy;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use serenity::builder::CreateEmbed;
+use serenity::http::{Http, HttpError};
+use serenity::model::id::{ChannelId, GuildId, MessageId};
+use serenity::prelude::RwLock;
+use tracing::warn;
+
+use super::player::{self, PlayerState};
+use super::queue;
+use crate::database::{self, get_guild, get_guild_language};
+use crate::lang::Language;
+
+/// Most upcoming songs listed on the dashboard.
+const UPCOMING: usize = 10;
+
+/// Longest song title listed on the dashboard, in characters, so the upcoming
+/// songs always fit in an embed field.
+const MAX_TITLE: usize = 80;
+
+/// Wait before refreshing the dashboard, so bursts of queue events like
+/// adding a playlist only edit it once.
+const REFRESH_DELAY: Duration = Duration::from_secs(1);
+
+/// Code of the Discord error for messages that don't exist.
+const UNKNOWN_MESSAGE: isize = 10008;
+
+/// Request channel of every guild seen, so messages are not checked against
+/// the database. The channel is only changed through the `request_channel`
+/// setting, which calls [`forget_request_channel`].
+static REQUEST_CHANNELS: SyncLazy<RwLock<HashMap<GuildId, Option<ChannelId>>>> =
+    SyncLazy::new(|| RwLock::new(HashMap::new()));
+
+/// Request channel of a guild, if it has one.
+pub async fn request_channel(guild_id: GuildId) -> Option<ChannelId> {
+    if let Some(channel) = REQUEST_CHANNELS.read().await.get(&guild_id) {
+        return *channel;
+    }
+
+    let channel = get_guild(guild_id)
+        .await
+        .and_then(|guild| guild.request_channel)
+        .map(ChannelId);
+    REQUEST_CHANNELS.write().await.insert(guild_id, channel);
+    channel
+}
+
+/// Read the request channel of a guild from the database again after it
+/// changed.
+pub async fn forget_request_channel(guild_id: GuildId) {
+    REQUEST_CHANNELS.write().await.remove(&guild_id);
+}
+
+/// Title of an upcoming song, cut to [`MAX_TITLE`] characters.
+fn short_title(title: &str) -> String {
+    let mut short: String = title.chars().take(MAX_TITLE).collect();
+    if title.chars().count() > MAX_TITLE {
+        short.push('\u{2026}');
+    }
+    short
+}
+
+/// What the dashboard of a request channel shows.
+#[derive(Debug, Default)]
+struct Dashboard {
+    player: Option<PlayerState>,
+    upcoming: Vec<String>,
+    remaining: usize,
+}
+
+impl Dashboard {
+    async fn of(guild_id: GuildId) -> Self {
+        let queues = queue::get_queues().await;
+        let queue = match queue::get(&queues, guild_id) {
+            Some(queue) => queue.read().await,
+            None => return Self::default(),
+        };
+        let remaining = queue.remaining();
+        Self {
+            player: queue.player_state().await,
+            upcoming: remaining
+                .iter()
+                .take(UPCOMING)
+                .map(|song| short_title(&song.title().unwrap_or_default()))
+                .collect(),
+            remaining: remaining.len(),
+        }
+    }
+
+    fn embed<'a>(&self, e: &'a mut CreateEmbed, lang: Language) -> &'a mut CreateEmbed {
+        player::player_embed(e, self.player.as_ref(), lang);
+        if self.upcoming.is_empty() {
+            return e;
+        }
+
+        let mut lines: Vec<String> = self
+            .upcoming
+            .iter()
+            .enumerate()
+            .map(|(index, title)| format!("{}. {}", index + 1, title))
+            .collect();
+        if self.remaining > self.upcoming.len() {
+            let more = self.remaining - self.upcoming.len();
+            lines.push(
+                lang.translate("request_channel.more", json!({ "count": more }))
+                    .unwrap_or_default(),
+            );
+        }
+        e.field(
+            lang.get("request_channel.upcoming"),
+            lines.join("\n"),
+            false,
+        )
+    }
+}
+
+/// Send a new dashboard to a request channel.
+pub async fn create(
+    http: &Http, guild_id: GuildId, channel: ChannelId,
+) -> Result<MessageId, serenity::Error> {
+    let lang = get_guild_language(guild_id).await;
+    let dashboard = Dashboard::of(guild_id).await;
+    let message = channel
+        .send_message(http, |m| {
+            m.content(lang.get("request_channel.hint"));
+            m.embed(|e| dashboard.embed(e, lang));
+            m.components(|c| player::player_components(c, dashboard.player.as_ref()));
+            m
+        })
+        .await?;
+    Ok(message.id)
+}
+
+/// Whether an error is caused by a message that was deleted.
+fn is_unknown_message(err: &serenity::Error) -> bool {
+    match err {
+        serenity::Error::Http(err) => match &**err {
+            HttpError::UnsuccessfulRequest(response) => response.error.code == UNKNOWN_MESSAGE,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Show the queue on the dashboard of the guild, if it has a request channel.
+/// A new dashboard is sent when the previous one was deleted.
+async fn refresh(http: &Http, guild_id: GuildId) -> Result<(), serenity::Error> {
+    let settings = match get_guild(guild_id).await {
+        Some(settings) => settings,
+        None => return Ok(()),
+    };
+    let channel = match settings.request_channel {
+        Some(channel) => ChannelId(channel),
+        None => return Ok(()),
+    };
+    let lang = settings.language();
+    let dashboard = Dashboard::of(guild_id).await;
+
+    if let Some(message) = settings.request_message {
+        let edited = channel
+            .edit_message(http, message, |m| {
+                m.content(lang.get("request_channel.hint"));
+                m.embed(|e| dashboard.embed(e, lang));
+                m.components(|c| player::player_components(c, dashboard.player.as_ref()));
+                m
+            })
+            .await;
+        match edited {
+            Ok(_) => return Ok(()),
+            Err(err) if is_unknown_message(&err) => {},
+            Err(err) => return Err(err),
+        }
+    }
+
+    let message = create(http, guild_id, channel).await?;
+    if let Err(err) =
+        database::update_guild(guild_id, |g| g.request_message = Some(message.0)).await
+    {
+        warn!("Failed to save request channel dashboard: {:?}", err);
+    }
+    Ok(())
+}
+
+/// Refresh the dashboard of a guild shortly. `pending` prevents scheduling
+/// another refresh until this one starts.
+pub fn schedule_refresh(guild_id: GuildId, http: Arc<Http>, pending: Arc<AtomicBool>) {
+    if pending.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    tokio::spawn(async move {
+        tokio::time::sleep(REFRESH_DELAY).await;
+        pending.store(false, Ordering::SeqCst);
+        if let Err(err) = refresh(&http, guild_id).await {
+            warn!("Failed to refresh request channel dashboard: {:?}", err);
+        }
+    });
+}
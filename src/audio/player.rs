@@ -0,0 +1,285 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use hhmmss::Hhmmss;
+use progressing::clamping::Bar;
+use progressing::Baring;
+use serde_json::json;
+use serenity::builder::{CreateComponents, CreateEmbed};
+use serenity::client::Context;
+use serenity::model::channel::ReactionType;
+use serenity::model::id::GuildId;
+use serenity::model::interactions::message_component::{ButtonStyle, MessageComponentInteraction};
+use serenity::model::interactions::{
+    InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
+};
+use serenity::utils::Colour;
+use songbird::input::Metadata;
+
+use super::queue::{self, MediaQueueError};
+use crate::checks::{self, can_control_player, can_listen, SkipVote};
+use crate::constants::MUSIC_ICON;
+use crate::database::get_language;
+use crate::lang::{Language, LocalizedError};
+
+/// Interval between progress bar updates of the player message.
+const UPDATE_INTERVAL: Duration = Duration::from_secs(15);
+
+const BUTTON_PREFIX: &str = "player.";
+
+/// Snapshot of the playing track, used to render the player message.
+#[derive(Debug, Clone)]
+pub struct PlayerState {
+    pub metadata: Metadata,
+    pub position: Duration,
+    pub paused: bool,
+    pub repeat: bool,
+    pub has_previous: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlayerButton {
+    Back,
+    PlayPause,
+    Next,
+    Shuffle,
+    Loop,
+    Stop,
+}
+
+impl PlayerButton {
+    const ALL: [PlayerButton; 6] = [
+        PlayerButton::Back,
+        PlayerButton::PlayPause,
+        PlayerButton::Next,
+        PlayerButton::Shuffle,
+        PlayerButton::Loop,
+        PlayerButton::Stop,
+    ];
+
+    fn id(self) -> &'static str {
+        match self {
+            PlayerButton::Back => "player.back",
+            PlayerButton::PlayPause => "player.pause",
+            PlayerButton::Next => "player.next",
+            PlayerButton::Shuffle => "player.shuffle",
+            PlayerButton::Loop => "player.loop",
+            PlayerButton::Stop => "player.stop",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|button| button.id() == id)
+    }
+
+    fn emoji(self, state: &PlayerState) -> &'static str {
+        match self {
+            PlayerButton::Back => "\u{23ee}",
+            PlayerButton::PlayPause if state.paused => "\u{25b6}",
+            PlayerButton::PlayPause => "\u{23f8}",
+            PlayerButton::Next => "\u{23ed}",
+            PlayerButton::Shuffle => "\u{1f500}",
+            PlayerButton::Loop => "\u{1f501}",
+            PlayerButton::Stop => "\u{23f9}",
+        }
+    }
+
+    fn style(self, state: &PlayerState) -> ButtonStyle {
+        match self {
+            PlayerButton::Loop if state.repeat => ButtonStyle::Success,
+            PlayerButton::Stop => ButtonStyle::Danger,
+            _ => ButtonStyle::Secondary,
+        }
+    }
+}
+
+pub fn progress_bar(position: Duration, end: Duration) -> String {
+    let mut progress_bar = Bar::new();
+    progress_bar.set_len(35);
+    progress_bar.set(position.as_secs() as f64 / end.as_secs().max(1) as f64);
+    progress_bar.set_style("[\u{25ac}\u{29bf}\u{25ac}]");
+
+    format!("{} {} {}", position.hhmmss(), progress_bar, end.hhmmss())
+}
+
+pub fn player_embed<'a>(
+    e: &'a mut CreateEmbed, state: Option<&PlayerState>, lang: Language,
+) -> &'a mut CreateEmbed {
+    e.thumbnail(MUSIC_ICON);
+    e.color(Colour::DARK_PURPLE);
+    e.title(lang.get("player.title"));
+
+    let state = match state {
+        Some(state) => state,
+        None => {
+            e.description(lang.get("player.idle"));
+            return e;
+        },
+    };
+    let metadata = &state.metadata;
+
+    if let Some(url) = metadata.source_url.as_deref() {
+        e.url(url);
+    }
+    if let Some(title) = metadata.title.as_deref() {
+        e.field(lang.get("player.field.title"), title, false);
+    }
+    if let Some(artist) = metadata.artist.as_deref() {
+        e.field(lang.get("player.field.artist"), artist, true);
+    }
+    if let Some(duration) = metadata.duration {
+        e.field(
+            lang.get("player.field.duration"),
+            lang.format_duration(duration),
+            true,
+        );
+        e.description(format!(
+            "```\n{}\n```",
+            progress_bar(state.position, duration)
+        ));
+    }
+
+    let mut footer = Vec::new();
+    if state.paused {
+        footer.push(lang.get("player.paused"));
+    }
+    if state.repeat {
+        footer.push(lang.get("player.loop"));
+    }
+    if !footer.is_empty() {
+        e.footer(|f| f.text(footer.join(" \u{2022} ")));
+    }
+    e
+}
+
+pub fn player_components<'a>(
+    c: &'a mut CreateComponents, state: Option<&PlayerState>,
+) -> &'a mut CreateComponents {
+    let state = match state {
+        Some(state) => state,
+        None => return c,
+    };
+    c.create_action_row(|row| {
+        for button in PlayerButton::ALL.iter().copied() {
+            row.create_button(|b| {
+                b.custom_id(button.id());
+                b.style(button.style(state));
+                b.emoji(ReactionType::Unicode(button.emoji(state).into()));
+                b.disabled(button == PlayerButton::Back && !state.has_previous);
+                b
+            });
+        }
+        row
+    })
+}
+
+/// Keep the player message of a guild updated with the playback progress
+/// while something is playing. `running` prevents starting the task twice.
+pub fn spawn_updater(guild_id: GuildId, running: Arc<AtomicBool>) {
+    if running.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(UPDATE_INTERVAL).await;
+
+            if queue::update_player_message(guild_id).await != Some(true) {
+                break;
+            }
+        }
+        running.store(false, Ordering::SeqCst);
+    });
+}
+
+/// Handle a press of one of the player message buttons.
+pub async fn handle_component(
+    ctx: &Context, component: &MessageComponentInteraction,
+) -> Result<(), MediaQueueError> {
+    if !component.data.custom_id.starts_with(BUTTON_PREFIX) {
+        return Ok(());
+    }
+    let button = PlayerButton::from_id(&component.data.custom_id);
+    let (button, guild_id) = match (button, component.guild_id) {
+        (Some(button), Some(guild_id)) => (button, guild_id),
+        _ => return Ok(()),
+    };
+
+    // Listeners that are not DJs vote to skip songs
+    let user_id = component.user.id;
+    let vote = match button {
+        PlayerButton::Next => match can_listen(ctx, guild_id, user_id).await {
+            Ok(()) => checks::vote_skip(ctx, guild_id, user_id).await,
+            Err(reason) => Err(reason),
+        },
+        _ => can_control_player(ctx, guild_id, user_id)
+            .await
+            .map(|_| SkipVote::Skip),
+    };
+    let notice = match vote {
+        Ok(SkipVote::Skip) => None,
+        Ok(SkipVote::Pending { votes, needed }) => Some(LocalizedError::with(
+            "queue.skip_votes",
+            json!({ "votes": votes, "needed": needed }),
+        )),
+        Err(reason) => Some(reason),
+    };
+    if let Some(notice) = notice {
+        let locale = Some(component.locale.as_str());
+        let lang = get_language(user_id, Some(guild_id), locale).await;
+        let notice = notice.translate(lang);
+        component
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource);
+                r.interaction_response_data(|d| {
+                    d.content(notice);
+                    d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+                    d
+                });
+                r
+            })
+            .await?;
+        return Ok(());
+    }
+
+    component
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::DeferredUpdateMessage)
+        })
+        .await?;
+
+    let result = press(guild_id, button).await;
+    queue::update_player_message(guild_id).await;
+    result
+}
+
+async fn press(guild_id: GuildId, button: PlayerButton) -> Result<(), MediaQueueError> {
+    if button == PlayerButton::Next {
+        return queue::try_play_all(guild_id, true).await;
+    }
+
+    let queues = queue::get_queues().await;
+    let queue = queue::get(&queues, guild_id).ok_or(MediaQueueError::NotPlaying)?;
+
+    match button {
+        PlayerButton::Back => {
+            queue.write().await.back().await?;
+            let song = queue.read().await.play().await?;
+            queue.write().await.update_song(song);
+        },
+        PlayerButton::PlayPause => {
+            let mut queue = queue.write().await;
+            if queue.is_paused().await {
+                queue.resume()?;
+            } else {
+                queue.pause()?;
+            }
+        },
+        PlayerButton::Shuffle => queue.write().await.shuffle(),
+        PlayerButton::Loop => queue.write().await.toggle_repeat(),
+        PlayerButton::Stop => queue.write().await.clear()?,
+        PlayerButton::Next => {},
+    }
+    Ok(())
+}
@@ -1,12 +1,15 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::lazy::SyncLazy;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use serde_json::json;
 use serenity::async_trait;
 use serenity::http::Http;
 use serenity::model::channel::ChannelType;
-use serenity::model::id::{ChannelId, GuildId};
+use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
+use serenity::model::misc::Mentionable;
 use serenity::prelude::{Mutex, RwLock};
 use serenity::utils::Colour;
 use smallvec::SmallVec;
@@ -18,8 +21,13 @@ use thiserror::Error;
 use tokio::sync::{RwLockReadGuard, RwLockWriteGuard};
 use tracing::{debug, error, instrument, warn};
 
+use super::dashboard;
+use super::player::{self, PlayerState};
 use super::source::{self, MediaResource};
 use crate::constants::MUSIC_ICON;
+use crate::database::history::{self, PlayedSong};
+use crate::database::{get_guild, get_guild_language, get_user, timestamp};
+use crate::lang::LocalizedError;
 
 pub type QueuesType = HashMap<GuildId, RwLock<MediaQueue>>;
 
@@ -58,31 +66,80 @@ pub enum MediaQueueError {
     SerenityError(#[from] serenity::Error),
 }
 
+/// Message shown to users for a queue error, the `Display` text is only meant
+/// for logs.
+impl From<&MediaQueueError> for LocalizedError {
+    fn from(err: &MediaQueueError) -> Self {
+        let key = match err {
+            MediaQueueError::Empty => "error.queue_empty",
+            MediaQueueError::NoBack => "error.queue_no_back",
+            MediaQueueError::NotPlaying => "error.not_playing",
+            MediaQueueError::QueueEnd => "error.queue_end",
+            MediaQueueError::NoUrl => "error.media_url",
+            MediaQueueError::ChannelPlayFailure => "error.channel_play",
+            MediaQueueError::CurrentChannelError => "error.current_channel",
+            MediaQueueError::Input(_) => "error.media_input",
+            MediaQueueError::Track(_) => "error.track",
+            MediaQueueError::SerenityError(_) => "error.discord",
+        };
+        LocalizedError::new(key)
+    }
+}
+
+/// The song being played, until it is recorded in the play history.
+#[derive(Debug, Clone)]
+struct NowPlaying {
+    track: TrackHandle,
+    media: MediaResource,
+    started_at: i64,
+    started: Instant,
+}
+
 #[derive(Debug, Clone)]
 pub struct MediaQueue {
     repeat: bool,
+    /// Whether the queue was shuffled since it started.
+    shuffled: bool,
     volume: f32,
     curr: usize,
     inner: SmallVec<[MediaResource; 5]>,
     curr_handle: Option<TrackHandle>,
+    now_playing: Option<NowPlaying>,
+    /// Listeners that voted to skip the current song.
+    skip_votes: HashSet<UserId>,
     handler_lock: Option<Arc<Mutex<Call>>>,
     channel: Option<ChannelId>,
     voice_channel: Option<ChannelId>,
     http: Option<Arc<Http>>,
+    guild_id: Option<GuildId>,
+    player_message: Option<MessageId>,
+    /// Request channel of the guild, where the dashboard replaces player
+    /// messages.
+    request_channel: Option<ChannelId>,
+    player_updater: Arc<AtomicBool>,
+    dashboard_pending: Arc<AtomicBool>,
 }
 
 impl Default for MediaQueue {
     fn default() -> Self {
         Self {
             repeat: false,
+            shuffled: false,
             volume: 1.0,
             curr: 0,
             inner: SmallVec::new(),
             curr_handle: None,
+            now_playing: None,
+            skip_votes: HashSet::new(),
             handler_lock: None,
             channel: None,
             voice_channel: None,
             http: None,
+            guild_id: None,
+            player_message: None,
+            request_channel: None,
+            player_updater: Arc::new(AtomicBool::new(false)),
+            dashboard_pending: Arc::new(AtomicBool::new(false)),
         }
     }
 }
@@ -90,16 +147,26 @@ impl Default for MediaQueue {
 impl MediaQueue {
     pub fn toggle_repeat(&mut self) {
         self.repeat = !self.repeat;
+        self.changed();
     }
 
     pub fn repeat(&self) -> bool {
         self.repeat
     }
 
+    pub fn shuffled(&self) -> bool {
+        self.shuffled
+    }
+
     pub fn current(&self) -> Option<&MediaResource> {
         self.inner.get(self.curr)
     }
 
+    /// Position of the current song in the tracks of the queue.
+    pub fn current_index(&self) -> usize {
+        self.curr
+    }
+
     pub fn get_tracks(&self) -> &SmallVec<[MediaResource; 5]> {
         &self.inner
     }
@@ -124,6 +191,8 @@ impl MediaQueue {
 
         debug!("Mixing queue");
         self.remaining_mut().shuffle(&mut thread_rng());
+        self.shuffled = true;
+        self.changed();
     }
 
     pub async fn next(&mut self) -> Result<(), MediaQueueError> {
@@ -141,11 +210,20 @@ impl MediaQueue {
         Ok(())
     }
 
+    /// Count the vote of a listener to skip the current song. Only the votes
+    /// of the current `listeners` count, and the number of them is given.
+    pub fn vote_skip(&mut self, user_id: UserId, listeners: &[UserId]) -> usize {
+        self.skip_votes.insert(user_id);
+        self.skip_votes.retain(|voter| listeners.contains(voter));
+        self.skip_votes.len()
+    }
+
     pub fn pause(&mut self) -> Result<(), MediaQueueError> {
         self.curr_handle
             .as_ref()
             .ok_or(MediaQueueError::NotPlaying)?
             .pause()?;
+        self.changed();
         Ok(())
     }
 
@@ -154,6 +232,7 @@ impl MediaQueue {
             .as_ref()
             .ok_or(MediaQueueError::NotPlaying)?
             .play()?;
+        self.changed();
         Ok(())
     }
 
@@ -163,6 +242,7 @@ impl MediaQueue {
             .ok_or(MediaQueueError::NotPlaying)?
             .stop()?;
         self.curr_handle = None;
+        self.finish_song(None);
         Ok(())
     }
 
@@ -170,6 +250,8 @@ impl MediaQueue {
         let _ = self.stop();
         self.curr = 0;
         self.inner = SmallVec::new();
+        self.shuffled = false;
+        self.changed();
         Ok(())
     }
 
@@ -194,6 +276,13 @@ impl MediaQueue {
         self.curr_handle.is_some()
     }
 
+    pub async fn is_paused(&self) -> bool {
+        match self.track_info().await {
+            Ok(state) => state.playing == PlayMode::Pause,
+            Err(_) => false,
+        }
+    }
+
     pub async fn track_info(&self) -> Result<Box<TrackState>, MediaQueueError> {
         let state = self
             .curr_handle
@@ -215,6 +304,7 @@ impl MediaQueue {
 
     pub fn add(&mut self, audio: MediaResource) {
         self.inner.push(audio);
+        self.changed();
     }
 
     pub fn seek(&mut self, position: Duration) -> Result<(), MediaQueueError> {
@@ -239,6 +329,15 @@ impl MediaQueue {
         self.http = Some(http.clone());
         self.channel = Some(channel);
         self.voice_channel = Some(voice_channel);
+        self.guild_id = Some(guild_id);
+
+        if let Some(guild) = get_guild(guild_id).await {
+            self.volume = f32::from(guild.default_volume) / 100.0;
+            if let Some(channel) = guild.announcement_channel {
+                self.channel = Some(ChannelId(channel));
+            }
+            self.request_channel = guild.request_channel.map(ChannelId);
+        }
 
         handler_lock
             .lock()
@@ -247,12 +346,107 @@ impl MediaQueue {
         Ok(())
     }
 
-    pub async fn update_song(&mut self, song: TrackHandle) {
-        if let Err(err) = self.send_song_message(&song).await {
-            warn!("Failed to send song message {:?}", err);
+    /// Queue event, like added songs or a paused player. Refreshes the
+    /// dashboard of the request channel of the guild.
+    pub fn changed(&self) {
+        if let (Some(guild_id), Some(http)) = (self.guild_id, self.http.clone()) {
+            dashboard::schedule_refresh(guild_id, http, self.dashboard_pending.clone());
         }
+    }
 
+    /// Start showing a new song. The player message is not updated, callers
+    /// update it with [`update_player_message`] after releasing the queue.
+    pub fn update_song(&mut self, song: TrackHandle) {
+        // Skipped songs are replaced without an end event
+        self.finish_song(None);
+        self.skip_votes.clear();
+        self.now_playing = self.current().map(|media| NowPlaying {
+            track: song.clone(),
+            media: media.clone(),
+            started_at: timestamp(),
+            started: Instant::now(),
+        });
         self.curr_handle = Some(song);
+        self.notify_requester();
+
+        if let Some(guild_id) = self.guild_id {
+            player::spawn_updater(guild_id, self.player_updater.clone());
+        }
+    }
+
+    /// Record the playing song in the play history of the guild. `play_time`
+    /// is how long it played, or the time since it started up to its length
+    /// when unknown.
+    fn finish_song(&mut self, play_time: Option<Duration>) {
+        let song = match self.now_playing.take() {
+            Some(song) => song,
+            None => return,
+        };
+        let guild_id = match self.guild_id {
+            Some(guild_id) => guild_id,
+            None => return,
+        };
+        let listened = play_time.unwrap_or_else(|| {
+            let elapsed = song.started.elapsed();
+            song.media
+                .length()
+                .map_or(elapsed, |length| elapsed.min(length))
+        });
+
+        let entry = PlayedSong::new(guild_id, &song.media, song.started_at, listened);
+        tokio::spawn(async move {
+            if let Err(err) = history::record(entry).await {
+                warn!("Failed to record played song: {:?}", err);
+            }
+        });
+    }
+
+    /// Record a song whose track ended, unless another song already replaced
+    /// it.
+    pub fn track_ended(&mut self, track: &TrackHandle, play_time: Duration) {
+        let playing = self.now_playing.as_ref().map(|song| song.track.uuid());
+        if playing == Some(track.uuid()) {
+            self.finish_song(Some(play_time));
+        }
+    }
+
+    /// Tell the requester of the current song that it started playing, when
+    /// they opted in to direct message notifications.
+    fn notify_requester(&self) {
+        let song = match self.current() {
+            Some(song) => song,
+            None => return,
+        };
+        let (requester, http, channel) =
+            match (song.requester, self.http.clone(), self.voice_channel) {
+                (Some(requester), Some(http), Some(channel)) => (requester, http, channel),
+                _ => return,
+            };
+        let title = song.title().unwrap_or_default();
+
+        tokio::spawn(async move {
+            let user = match get_user(requester).await {
+                Some(user) if user.dm_notifications => user,
+                _ => return,
+            };
+            let lang = user.lang.unwrap_or_default();
+            let text = lang.translate(
+                "player.notification",
+                json!({ "title": title, "channel": channel.mention().to_string() }),
+            );
+            let text = match text {
+                Ok(text) => text,
+                Err(err) => return warn!("Failed to translate notification: {:?}", err),
+            };
+
+            let sent = match requester.create_dm_channel(&http).await {
+                Ok(dm) => dm.say(&http, text).await.map(|_| ()),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = sent {
+                warn!("Failed to send song notification: {:?}", err);
+            }
+        });
     }
 
     #[instrument]
@@ -309,83 +503,120 @@ impl MediaQueue {
         Ok(song)
     }
 
-    pub async fn send_queue_error(&self, err: MediaQueueError) -> Result<(), MediaQueueError> {
+    /// Where announcements of the queue are sent, and the request channel of
+    /// the guild.
+    fn announcement_channel(
+        &self,
+    ) -> Result<(Arc<Http>, ChannelId, Option<ChannelId>), MediaQueueError> {
         let http = self
             .http
             .clone()
             .ok_or(MediaQueueError::CurrentChannelError)?;
         let channel = self.channel.ok_or(MediaQueueError::CurrentChannelError)?;
+        Ok((http, channel, self.request_channel))
+    }
 
-        match err {
-            MediaQueueError::Empty => {
-                let _ = channel
-                    .send_message(&http, |m| {
-                        m.embed(|e| {
-                            e.thumbnail(MUSIC_ICON);
-                            e.color(Colour::DARK_PURPLE);
-                            e.title("Finished queue");
-                            e.description("Finished all elements in the sound player queue");
-
-                            e
-                        });
-                        m
-                    })
-                    .await;
-            },
-            _ => {
-                let _ = channel
-                    .send_message(&http, |m| {
-                        m.embed(|e| {
-                            e.thumbnail(MUSIC_ICON);
-                            e.color(Colour::DARK_RED);
-                            e.title("Music player error");
-                            e.description(format!("{:?}", err));
-
-                            e
-                        });
-                        m
-                    })
-                    .await;
-            },
-        }
-        Ok(())
+    pub async fn player_state(&self) -> Option<PlayerState> {
+        let handle = self.curr_handle.as_ref()?;
+        let info = handle.get_info().await.ok()?;
+        Some(PlayerState {
+            metadata: handle.metadata().clone(),
+            position: info.position,
+            paused: info.playing == PlayMode::Pause,
+            repeat: self.repeat,
+            has_previous: self.curr > 0,
+        })
     }
 
-    async fn send_song_message(&self, song: &TrackHandle) -> Result<(), MediaQueueError> {
+    /// What the player message should show, collected so it can be sent
+    /// after releasing the queue. `None` when the request channel dashboard
+    /// replaces the player message.
+    pub async fn player_message(&self) -> Result<Option<PlayerMessage>, MediaQueueError> {
         let http = self
             .http
             .clone()
             .ok_or(MediaQueueError::CurrentChannelError)?;
         let channel = self.channel.ok_or(MediaQueueError::CurrentChannelError)?;
-        let msg_err = channel
-            .send_message(&http, |m| {
-                m.embed(|e| {
-                    e.thumbnail(MUSIC_ICON);
-                    e.color(Colour::DARK_PURPLE);
-                    e.title("Now Playing");
+        if self.request_channel == Some(channel) {
+            return Ok(None);
+        }
+        Ok(Some(PlayerMessage {
+            http,
+            channel,
+            guild_id: self.guild_id,
+            message: self.player_message,
+            state: self.player_state().await,
+        }))
+    }
 
-                    if let Some(url) = song.metadata().source_url.as_deref() {
-                        e.url(url);
-                    }
-                    if let Some(title) = song.metadata().title.as_deref() {
-                        e.field("Title", title, false);
-                    }
-                    if let Some(artist) = song.metadata().artist.as_deref() {
-                        e.field("Artist", artist, true);
-                    }
-                    if let Some(duration) = song.metadata().duration {
-                        e.field("Duration", humantime::format_duration(duration), true);
-                    }
-                    e
-                });
-                m
-            })
-            .await;
-        if let Err(err) = msg_err {
-            warn!("Failed to send next song message: {:?}", err);
+    /// Store the message showing the player after sending a
+    /// [`PlayerMessage`], unless another one replaced `previous` meanwhile.
+    pub fn set_player_message(&mut self, previous: Option<MessageId>, message: Option<MessageId>) {
+        if self.player_message == previous {
+            self.player_message = message;
         }
+    }
+}
 
-        Ok(())
+/// Snapshot of the player message of a guild, sent without holding its
+/// queue.
+pub struct PlayerMessage {
+    http: Arc<Http>,
+    channel: ChannelId,
+    guild_id: Option<GuildId>,
+    message: Option<MessageId>,
+    state: Option<PlayerState>,
+}
+
+impl PlayerMessage {
+    /// Message currently showing the player, if any.
+    pub fn message(&self) -> Option<MessageId> {
+        self.message
+    }
+
+    /// Edit the player message, or send a new one when it was deleted. Once
+    /// the queue is idle the buttons are removed and the next song gets a new
+    /// message. Gives the message that shows the player afterwards.
+    pub async fn send(self) -> Result<Option<MessageId>, MediaQueueError> {
+        let Self {
+            http,
+            channel,
+            guild_id,
+            message,
+            state,
+        } = self;
+        // Announcements have no author, so they use the guild language
+        let lang = match guild_id {
+            Some(guild_id) => get_guild_language(guild_id).await,
+            None => Default::default(),
+        };
+
+        if let Some(message_id) = message {
+            let edited = channel
+                .edit_message(&http, message_id, |m| {
+                    m.embed(|e| player::player_embed(e, state.as_ref(), lang));
+                    m.components(|c| player::player_components(c, state.as_ref()));
+                    m
+                })
+                .await;
+            match edited {
+                Ok(_) if state.is_none() => return Ok(None),
+                Ok(_) => return Ok(Some(message_id)),
+                Err(err) => warn!("Failed to edit player message: {:?}", err),
+            }
+        }
+
+        if state.is_none() {
+            return Ok(None);
+        }
+        let msg = channel
+            .send_message(&http, |m| {
+                m.embed(|e| player::player_embed(e, state.as_ref(), lang));
+                m.components(|c| player::player_components(c, state.as_ref()));
+                m
+            })
+            .await?;
+        Ok(Some(msg.id))
     }
 }
 
@@ -405,28 +636,125 @@ pub fn get(queues: &QueuesType, id: GuildId) -> Option<&RwLock<MediaQueue>> {
     queues.get(&id)
 }
 
-pub async fn try_play_all(guild_id: GuildId, next: bool) -> Result<(), MediaQueueError> {
-    let queues = get_queues().await;
-    let queue = get(&queues, guild_id).ok_or(MediaQueueError::Empty)?;
+/// Announce an error of the queue of a guild, like reaching its end. The
+/// queue is only locked to find where to send it.
+pub async fn send_queue_error(
+    guild_id: GuildId, err: MediaQueueError,
+) -> Result<(), MediaQueueError> {
+    let (http, channel, request_channel) = {
+        let queues = get_queues().await;
+        let queue = get(&queues, guild_id)
+            .ok_or(MediaQueueError::NotPlaying)?
+            .read()
+            .await;
+        queue.announcement_channel()?
+    };
+    // Announcements have no author, so they use the guild language
+    let lang = get_guild_language(guild_id).await;
+
+    match err {
+        // The dashboard already shows the finished queue
+        MediaQueueError::Empty if request_channel == Some(channel) => {},
+        MediaQueueError::Empty => {
+            let _ = channel
+                .send_message(&http, |m| {
+                    m.embed(|e| {
+                        e.thumbnail(MUSIC_ICON);
+                        e.color(Colour::DARK_PURPLE);
+                        e.title(lang.get("queue.finished.title"));
+                        e.description(lang.get("queue.finished"));
+
+                        e
+                    });
+                    m
+                })
+                .await;
+        },
+        _ => {
+            let description = lang
+                .translate(
+                    "player.error",
+                    json!({ "error": LocalizedError::from(&err).translate(lang) }),
+                )
+                .unwrap_or_else(|_| err.to_string());
+            let _ = channel
+                .send_message(&http, |m| {
+                    m.embed(|e| {
+                        e.thumbnail(MUSIC_ICON);
+                        e.color(Colour::DARK_RED);
+                        e.title(lang.get("player.error.title"));
+                        e.description(description);
+
+                        e
+                    });
+                    m
+                })
+                .await;
+        },
+    }
+    Ok(())
+}
 
-    if next {
-        queue.write().await.next().await?;
+/// Show the state of the queue of a guild in its player message, editing the
+/// previous one when possible. The request channel dashboard is refreshed
+/// too, and replaces the player message in its channel. The queue is only
+/// locked to collect what the message shows and to store the sent message,
+/// not while it is sent. Gives whether the guild is still playing, or `None`
+/// once its queue is gone.
+pub async fn update_player_message(guild_id: GuildId) -> Option<bool> {
+    let (playing, message) = {
+        let queues = get_queues().await;
+        let queue = get(&queues, guild_id)?.read().await;
+        queue.changed();
+        (queue.is_playing(), queue.player_message().await)
+    };
+    let message = match message {
+        Ok(Some(message)) => message,
+        Ok(None) => return Some(playing),
+        Err(err) => {
+            warn!("Failed to update player message: {:?}", err);
+            return Some(playing);
+        },
+    };
+
+    let previous = message.message();
+    match message.send().await {
+        Ok(sent) => {
+            let queues = get_queues().await;
+            if let Some(queue) = get(&queues, guild_id) {
+                queue.write().await.set_player_message(previous, sent);
+            }
+        },
+        Err(err) => warn!("Failed to update player message: {:?}", err),
     }
+    Some(playing)
+}
 
-    let mut song = queue.read().await.play().await;
-    while let Err(err) = song {
-        error!("Failed to play song {:?}", err);
+pub async fn try_play_all(guild_id: GuildId, next: bool) -> Result<(), MediaQueueError> {
+    {
+        let queues = get_queues().await;
+        let queue = get(&queues, guild_id).ok_or(MediaQueueError::Empty)?;
 
-        if queue.write().await.next().await.is_err() {
-            return Err(MediaQueueError::QueueEnd);
+        if next {
+            queue.write().await.next().await?;
         }
 
-        song = queue.read().await.play().await;
-    }
-    if let Ok(song) = song {
-        queue.write().await.update_song(song).await;
+        let mut song = queue.read().await.play().await;
+        while let Err(err) = song {
+            error!("Failed to play song {:?}", err);
+
+            if queue.write().await.next().await.is_err() {
+                return Err(MediaQueueError::QueueEnd);
+            }
+
+            song = queue.read().await.play().await;
+        }
+        if let Ok(song) = song {
+            queue.write().await.update_song(song);
+        }
     }
 
+    update_player_message(guild_id).await;
     Ok(())
 }
 
@@ -447,17 +775,26 @@ impl VoiceEventHandler for SongEndNotifier {
                     handle.metadata().title,
                     state
                 );
-
-                if let Err(err) = try_play_all(self.guild_id, true).await {
+                {
                     let queues = get_queues().await;
                     if let Some(queue) = get(&queues, self.guild_id) {
-                        if let Err(err) = queue.read().await.send_queue_error(err).await {
-                            warn!("Failed to display queue error: {:?}", err);
-                        }
-                        if let Err(err) = queue.write().await.clear() {
-                            warn!("Failed to clear queue: {:?}", err);
+                        queue.write().await.track_ended(handle, state.play_time);
+                    }
+                }
+
+                if let Err(err) = try_play_all(self.guild_id, true).await {
+                    if let Err(err) = send_queue_error(self.guild_id, err).await {
+                        warn!("Failed to display queue error: {:?}", err);
+                    }
+                    {
+                        let queues = get_queues().await;
+                        if let Some(queue) = get(&queues, self.guild_id) {
+                            if let Err(err) = queue.write().await.clear() {
+                                warn!("Failed to clear queue: {:?}", err);
+                            }
                         }
                     }
+                    update_player_message(self.guild_id).await;
                     return Some(Event::Cancel);
                 }
             }
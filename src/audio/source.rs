@@ -1,19 +1,67 @@
 use std::error::Error;
 use std::io::{BufRead, BufReader, Read};
 use std::process::{Command, Stdio};
+use std::str::FromStr;
 use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 use serenity::async_trait;
+use serenity::model::id::UserId;
 use songbird::input::error::{Error as SongbirdError, Result as SongbirdResult};
 use songbird::input::restartable::Restart;
 use songbird::input::{Codec, Container, Input, Metadata, Reader, Restartable};
 use tokio::process::Command as TokioCommand;
 use tokio::task;
 use tracing::debug;
+use youtube_music::SearchFilter;
+
+use crate::lang::LocalizedError;
 
 const YOUTUBE_DL_COMMAND: &str = "youtube-dl";
 
+/// Where songs requested by name are searched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SearchSource {
+    YouTubeMusic,
+    YouTube,
+    SoundCloud,
+}
+
+impl SearchSource {
+    pub const ALL: [SearchSource; 3] = [
+        SearchSource::YouTubeMusic,
+        SearchSource::YouTube,
+        SearchSource::SoundCloud,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SearchSource::YouTubeMusic => "youtube_music",
+            SearchSource::YouTube => "youtube",
+            SearchSource::SoundCloud => "soundcloud",
+        }
+    }
+}
+
+impl Default for SearchSource {
+    fn default() -> Self {
+        SearchSource::YouTubeMusic
+    }
+}
+
+impl FromStr for SearchSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace(' ', "_");
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|source| source.name() == name)
+            .ok_or_else(|| format!("Unknown search source '{}'", s))
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MediaResource {
     pub id: Option<String>,
@@ -23,16 +71,60 @@ pub struct MediaResource {
     pub duration: Option<f64>,
     pub view_count: Option<u64>,
     pub uploader: Option<String>,
+    pub track: Option<String>,
+    pub artist: Option<String>,
+    pub thumbnail: Option<String>,
+    /// Page of the media, `url` can be a direct link to the audio.
+    pub webpage_url: Option<String>,
+    /// Site the media was extracted from, like "Youtube".
+    pub extractor_key: Option<String>,
+    /// Site of a playlist entry, entries have no `extractor_key`.
+    pub ie_key: Option<String>,
+    #[serde(default)]
+    pub requester: Option<UserId>,
+    /// When the media was queued, in UNIX seconds.
+    #[serde(default)]
+    pub added_at: Option<i64>,
 }
 
 impl MediaResource {
     pub async fn with_query<S: AsRef<str>>(query: S) -> Result<Self, Box<dyn Error>> {
-        let results = youtube_music::search(query).await?;
-        let result = results.get(0).ok_or("No results found for your query.")?;
-        let meta = ytdl_metadata(&result.video_id)
+        let results = youtube_music::search(query, SearchFilter::Songs, 1).await?;
+        let video_id = results
+            .get(0)
+            .and_then(|result| result.video_id.as_ref())
+            .ok_or_else(|| LocalizedError::new("error.no_results"))?;
+        Self::with_video_id(video_id).await
+    }
+
+    /// Search a song by name in `source`.
+    pub async fn search<S: AsRef<str>>(
+        query: S, source: SearchSource,
+    ) -> Result<Self, Box<dyn Error>> {
+        let prefix = match source {
+            SearchSource::YouTubeMusic => return Self::with_query(query).await,
+            SearchSource::YouTube => "ytsearch1",
+            SearchSource::SoundCloud => "scsearch1",
+        };
+        let meta = ytdl_metadata(format!("{}:{}", prefix, query.as_ref()))
+            .await
+            .map_err(|err| format!("`youtube-dl` error {:?}", err))?;
+        let meta = meta
+            .into_iter()
+            .next()
+            .ok_or_else(|| LocalizedError::new("error.no_results"))?;
+
+        Ok(meta)
+    }
+
+    pub async fn with_video_id<S: AsRef<str>>(video_id: S) -> Result<Self, Box<dyn Error>> {
+        let meta = ytdl_metadata(video_id)
             .await
             .map_err(|err| format!("`youtube-dl` error {:?}", err))?;
-        let meta = meta.get(0).ok_or("No results found for your query.")?.clone();
+        let meta = meta
+            .get(0)
+            .ok_or_else(|| LocalizedError::new("error.no_results"))?
+            .clone();
 
         Ok(meta)
     }
@@ -44,9 +136,24 @@ impl MediaResource {
         self.url.clone()
     }
 
+    /// Length of the media, unknown for live streams and some sources.
+    pub fn length(&self) -> Option<Duration> {
+        self.duration
+            .filter(|secs| secs.is_finite() && *secs > 0.0)
+            .map(Duration::from_secs_f64)
+    }
+
     pub async fn url(&self) -> Option<String> {
         self.url.clone()
     }
+
+    /// Whether the media is a YouTube video.
+    pub fn is_youtube(&self) -> bool {
+        self.extractor_key
+            .iter()
+            .chain(&self.ie_key)
+            .any(|key| key == "Youtube")
+    }
 }
 
 pub async fn ytdl_metadata<S>(uri: S) -> SongbirdResult<Vec<MediaResource>>
@@ -0,0 +1,64 @@
+use std::error::Error;
+use std::lazy::SyncLazy;
+
+use regex::Regex;
+
+static YOUTUBE_MUSIC_URL: SyncLazy<Regex> = SyncLazy::new(|| {
+    let regex = r"^https?://music\.youtube\.com/(browse|channel)/([0-9a-zA-Z_-]+)";
+    Regex::new(regex).expect("Failed to compile YouTube Music URL regex")
+});
+
+const PARSE_ERR: &str = "Failed to parse YouTube Music URL";
+
+/// Maximum amount of top songs queued from an artist link.
+const ARTIST_SONGS_LIMIT: usize = 25;
+
+/// Maximum amount of songs queued from a playlist link.
+const PLAYLIST_SONGS_LIMIT: usize = 100;
+
+/// A YouTube Music link to a list of tracks.
+enum Link<'a> {
+    /// Browse id of an album, starting with `MPREb_`.
+    Album(&'a str),
+    /// Browse id of a playlist, starting with `VL`.
+    Playlist(&'a str),
+    /// Channel id of an artist.
+    Artist(&'a str),
+}
+
+/// Tracks listed by a link. Other browse pages, like moods or charts, are not
+/// handled and are left to `youtube-dl`.
+fn parse_link(url: &str) -> Option<Link<'_>> {
+    let captures = YOUTUBE_MUSIC_URL.captures(url)?;
+    let id = captures.get(2)?.as_str();
+    match captures.get(1)?.as_str() {
+        "browse" if id.starts_with("MPREb_") => Some(Link::Album(id)),
+        "browse" if id.starts_with("VL") => Some(Link::Playlist(id)),
+        "channel" => Some(Link::Artist(id)),
+        _ => None,
+    }
+}
+
+pub fn is_youtube_music_url<S>(url: S) -> bool
+where
+    S: AsRef<str>, {
+    parse_link(url.as_ref()).is_some()
+}
+
+/// Get the video ids of the tracks of a YouTube Music album, playlist or
+/// artist link.
+pub async fn get_video_ids<S>(query: S) -> Result<Vec<String>, Box<dyn Error>>
+where
+    S: AsRef<str>, {
+    let collection = match parse_link(query.as_ref()).ok_or(PARSE_ERR)? {
+        Link::Album(id) => youtube_music::album(id).await?,
+        Link::Playlist(id) => youtube_music::playlist(id, PLAYLIST_SONGS_LIMIT).await?,
+        Link::Artist(id) => youtube_music::artist(id, ARTIST_SONGS_LIMIT).await?,
+    };
+
+    Ok(collection
+        .tracks
+        .into_iter()
+        .filter_map(|track| track.video_id)
+        .collect())
+}
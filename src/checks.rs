@@ -0,0 +1,233 @@
+use serenity::client::Context;
+use serenity::framework::standard::macros::check;
+use serenity::framework::standard::{Args, CommandOptions, Reason};
+use serenity::model::channel::Message;
+use serenity::model::guild::Guild as DiscordGuild;
+use serenity::model::id::{ChannelId, GuildId, RoleId, UserId};
+
+use crate::audio::queue;
+use crate::database::get_guild;
+use crate::database::guild::Guild;
+use crate::lang::LocalizedError;
+use crate::ratelimit;
+
+/// Reason of the checks of users that run commands too fast.
+pub const RATE_LIMITED: &str = "error.rate_limited";
+
+/// Whether `user_id` listens to the music player of `guild_id`. When the bot
+/// is playing, only listeners in its voice channel can use it.
+pub async fn can_listen(
+    ctx: &Context, guild_id: GuildId, user_id: UserId,
+) -> Result<(), LocalizedError> {
+    let guild = guild_id
+        .to_guild_cached(&ctx.cache)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
+
+    let queues = queue::get_queues().await;
+    let bot_channel = match queue::get(&queues, guild_id) {
+        Some(queue) => queue.read().await.voice_channel(),
+        None => return Ok(()),
+    };
+
+    let user_channel = guild
+        .voice_states
+        .get(&user_id)
+        .and_then(|voice_state| voice_state.channel_id);
+
+    match bot_channel {
+        Some(channel) if user_channel != Some(channel) => {
+            Err(LocalizedError::new("error.not_in_bot_channel"))
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Whether `user_id` has the DJ role of the guild, or administers it.
+async fn is_dj(ctx: &Context, guild: &DiscordGuild, settings: &Guild, user_id: UserId) -> bool {
+    let admin = guild
+        .member_permissions(ctx, user_id)
+        .await
+        .map_or(false, |permissions| permissions.administrator());
+    let role = match settings.dj_role {
+        Some(role) => RoleId(role),
+        None => return admin,
+    };
+    admin
+        || guild
+            .member(ctx, user_id)
+            .await
+            .map_or(false, |member| member.roles.contains(&role))
+}
+
+/// Whether `user_id` may control the music player of `guild_id`. They must
+/// listen to it, and have the DJ role when the guild has one.
+pub async fn can_control_player(
+    ctx: &Context, guild_id: GuildId, user_id: UserId,
+) -> Result<(), LocalizedError> {
+    can_listen(ctx, guild_id, user_id).await?;
+
+    let settings = match get_guild(guild_id).await {
+        Some(settings) if settings.dj_role.is_some() => settings,
+        _ => return Ok(()),
+    };
+    let guild = guild_id
+        .to_guild_cached(&ctx.cache)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
+    if is_dj(ctx, &guild, &settings, user_id).await {
+        Ok(())
+    } else {
+        Err(LocalizedError::new("error.dj_role"))
+    }
+}
+
+/// Outcome of asking to skip the current song.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipVote {
+    Skip,
+    /// More listeners must vote to skip the song.
+    Pending {
+        votes: usize,
+        needed: usize,
+    },
+}
+
+/// Votes needed to skip a song, a percentage of the listeners.
+fn needed_votes(listeners: usize, threshold: u8) -> usize {
+    let needed = (listeners * usize::from(threshold) + 99) / 100;
+    needed.max(1)
+}
+
+/// Vote to skip the current song of `guild_id`. DJs and the requester of the
+/// song skip it right away, other listeners skip it once enough of them
+/// voted, as set by the vote skip threshold of the guild.
+pub async fn vote_skip(
+    ctx: &Context, guild_id: GuildId, user_id: UserId,
+) -> Result<SkipVote, LocalizedError> {
+    let guild = guild_id
+        .to_guild_cached(&ctx.cache)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
+    let settings = get_guild(guild_id)
+        .await
+        .unwrap_or_else(|| Guild::new(guild_id));
+    if is_dj(ctx, &guild, &settings, user_id).await {
+        return Ok(SkipVote::Skip);
+    }
+
+    let bot_id = ctx.cache.current_user_id().await;
+    let queues = queue::get_queues().await;
+    let mut queue = match queue::get(&queues, guild_id) {
+        Some(queue) => queue.write().await,
+        None => return Ok(SkipVote::Skip),
+    };
+    let requester = queue.current().and_then(|song| song.requester);
+    let channel = match queue.voice_channel() {
+        Some(channel) if requester != Some(user_id) => channel,
+        _ => return Ok(SkipVote::Skip),
+    };
+
+    let listeners: Vec<UserId> = guild
+        .voice_states
+        .values()
+        .filter(|state| state.channel_id == Some(channel) && state.user_id != bot_id)
+        .filter(|state| {
+            state
+                .member
+                .as_ref()
+                .map_or(true, |member| !member.user.bot)
+        })
+        .map(|state| state.user_id)
+        .collect();
+    let votes = queue.vote_skip(user_id, &listeners);
+    let needed = needed_votes(listeners.len(), settings.vote_skip_threshold);
+    if votes >= needed {
+        Ok(SkipVote::Skip)
+    } else {
+        Ok(SkipVote::Pending { votes, needed })
+    }
+}
+
+/// Whether music commands can be used in `channel_id`, limited by the music
+/// channels of the guild settings.
+pub async fn can_use_channel(
+    guild_id: GuildId, channel_id: ChannelId,
+) -> Result<(), LocalizedError> {
+    let music_channels = match get_guild(guild_id).await {
+        Some(guild) => guild.music_channels,
+        None => return Ok(()),
+    };
+    if music_channels.is_empty() || music_channels.contains(&channel_id.0) {
+        Ok(())
+    } else {
+        Err(LocalizedError::new("error.music_channel"))
+    }
+}
+
+/// Count a use of a command by `user_id` against the rate limit of its
+/// bucket, if it has one.
+pub async fn check_rate_limit(
+    options: &CommandOptions, user_id: UserId,
+) -> Result<(), LocalizedError> {
+    let bucket = match options.bucket.and_then(ratelimit::find) {
+        Some(bucket) => bucket,
+        None => return Ok(()),
+    };
+    bucket
+        .hit(user_id)
+        .await
+        .map_err(|_| LocalizedError::new(RATE_LIMITED))
+}
+
+#[check]
+#[name = "Player"]
+async fn player_check(
+    ctx: &Context, msg: &Message, _: &mut Args, _: &CommandOptions,
+) -> Result<(), Reason> {
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+    can_control_player(ctx, guild_id, msg.author.id)
+        .await
+        .map_err(|reason| Reason::User(reason.key.to_string()))
+}
+
+#[check]
+#[name = "Listener"]
+async fn listener_check(
+    ctx: &Context, msg: &Message, _: &mut Args, _: &CommandOptions,
+) -> Result<(), Reason> {
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+    can_listen(ctx, guild_id, msg.author.id)
+        .await
+        .map_err(|reason| Reason::User(reason.key.to_string()))
+}
+
+#[check]
+#[name = "MusicChannel"]
+async fn music_channel_check(
+    _: &Context, msg: &Message, _: &mut Args, _: &CommandOptions,
+) -> Result<(), Reason> {
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+    can_use_channel(guild_id, msg.channel_id)
+        .await
+        .map_err(|reason| Reason::User(reason.key.to_string()))
+}
+
+#[check]
+#[name = "RateLimit"]
+async fn rate_limit_check(
+    _: &Context, msg: &Message, _: &mut Args, options: &CommandOptions,
+) -> Result<(), Reason> {
+    check_rate_limit(options, msg.author.id)
+        .await
+        .map_err(|reason| Reason::User(reason.key.to_string()))
+}
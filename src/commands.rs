@@ -2,6 +2,7 @@ mod audio;
 mod general;
 
 pub mod help;
+pub mod slash;
 
 pub use self::audio::*;
 pub use self::general::*;
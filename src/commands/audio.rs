@@ -1,26 +1,30 @@
 use serenity::framework::standard::macros::group;
 
-mod back;
-mod effects;
-mod fast_forward;
-mod join;
-mod leave;
-mod lyrics;
-mod next;
-mod pause;
-mod play;
-mod queue;
-mod repeat;
-mod resume;
-mod rewind;
-mod seek;
-mod shuffle;
-mod song;
-mod stop;
-mod volume;
+pub(super) mod back;
+pub(super) mod effects;
+pub(super) mod fast_forward;
+pub(super) mod history;
+pub(super) mod info;
+pub(super) mod join;
+pub(super) mod leave;
+pub(super) mod lyrics;
+pub(super) mod next;
+pub(super) mod pause;
+pub(crate) mod play;
+pub(super) mod queue;
+pub(super) mod repeat;
+pub(super) mod resume;
+pub(super) mod rewind;
+pub(super) mod seek;
+pub(super) mod shuffle;
+pub(super) mod song;
+pub(super) mod stop;
+pub(super) mod volume;
 
 use self::back::BACK_COMMAND;
 use self::fast_forward::FAST_FORWARD_COMMAND;
+use self::history::HISTORY_COMMAND;
+use self::info::INFO_COMMAND;
 use self::join::JOIN_COMMAND;
 use self::leave::LEAVE_COMMAND;
 use self::lyrics::LYRICS_COMMAND;
@@ -36,6 +40,7 @@ use self::shuffle::SHUFFLE_COMMAND;
 use self::song::SONG_COMMAND;
 use self::stop::STOP_COMMAND;
 use self::volume::VOLUME_COMMAND;
+use crate::checks::{MUSIC_CHANNEL_CHECK, RATE_LIMIT_CHECK};
 
 #[group]
 #[commands(
@@ -51,10 +56,13 @@ use self::volume::VOLUME_COMMAND;
     lyrics,
     volume,
     song,
+    info,
+    history,
     shuffle,
     back,
     fast_forward,
     rewind,
     repeat
 )]
+#[checks(MusicChannel, RateLimit)]
 struct Audio;
@@ -5,26 +5,43 @@ use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::channel::Message;
 
 use crate::audio::queue;
+use crate::checks::PLAYER_CHECK;
+use crate::invocation::Invocation;
+use crate::lang::LocalizedError;
 use crate::utils::send_translated_info;
 
 #[command]
 #[only_in(guilds)]
+#[checks(Player)]
 async fn back(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
-    let guild = msg.guild(&ctx.cache).await.ok_or("Failed to fetch guild")?;
+    run(ctx, &msg.into()).await
+}
+
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>) -> CommandResult {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
     let guild_id = guild.id;
 
-    let queues = queue::get_queues().await;
-    let queue = queue::get(&queues, guild_id).ok_or("No queue found for guild")?;
+    let remaining = {
+        let queues = queue::get_queues().await;
+        let queue =
+            queue::get(&queues, guild_id).ok_or_else(|| LocalizedError::new("error.no_queue"))?;
 
-    queue.write().await.back().await?;
-    let song = queue.read().await.play().await?;
-    queue.write().await.update_song(song).await;
+        queue.write().await.back().await?;
+        let song = queue.read().await.play().await?;
+        let mut queue = queue.write().await;
+        queue.update_song(song);
+        queue.remaining().len()
+    };
+    queue::update_player_message(guild_id).await;
 
     send_translated_info(
         "voice.update",
         "queue.back",
-        json!({"remaining": queue.read().await.remaining().len()}),
-        msg,
+        json!({ "remaining": remaining }),
+        invocation,
         ctx,
     )
     .await
@@ -8,25 +8,32 @@ use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::channel::Message;
 
 use crate::audio::queue;
+use crate::invocation::Invocation;
+use crate::lang::LocalizedError;
 use crate::utils::send_translated_info;
 
 #[command]
 #[only_in(guilds)]
-#[description = "Fast forward a portion of an audio.\nUses 5 seconds by default\n**Example:** `ff \
-                 30`"]
 #[aliases("ff", "fastforward", "advance")]
 async fn fast_forward(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let guild = msg.guild(&ctx.cache).await.ok_or("Failed to fetch guild")?;
+    run(ctx, &msg.into(), args.single().unwrap_or(5)).await
+}
+
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>, seconds: u64) -> CommandResult {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
     let guild_id = guild.id;
 
     let queues = queue::get_queues().await;
     let mut queue = queue::get(&queues, guild_id)
-        .ok_or("No queue found for guild")?
+        .ok_or_else(|| LocalizedError::new("error.no_queue"))?
         .write()
         .await;
 
     let mut position = queue.track_info().await?.position;
-    position += Duration::new(args.single().unwrap_or(5), 0);
+    position += Duration::from_secs(seconds);
 
     queue.seek(position)?;
 
@@ -34,7 +41,7 @@ async fn fast_forward(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
         "voice.update",
         "audio.seek",
         json!({ "position": position.hhmmss() }),
-        msg,
+        invocation,
         ctx,
     )
     .await
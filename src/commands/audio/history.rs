@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use hhmmss::Hhmmss;
+use serde_json::json;
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::channel::Message;
+use serenity::utils::Colour;
+
+use crate::constants;
+use crate::database::history::{self, PlayedSong};
+use crate::invocation::Invocation;
+use crate::lang::{Language, LocalizedError};
+use crate::paginator::Paginator;
+
+/// Songs listed on every page of the history.
+const PAGE_SIZE: usize = 10;
+
+/// Most recent songs shown in the history.
+const HISTORY_LIMIT: i64 = 100;
+
+/// Line of a played song, with how long it was listened to, its requester
+/// and when it started.
+fn song_line(lang: Language, number: usize, song: &PlayedSong) -> String {
+    let title = match song.title.as_str() {
+        "" => lang.get("song.unknown"),
+        title => title.to_string(),
+    };
+    let listened = Duration::from_secs(song.listened.max(0) as u64);
+    let mut details = vec![format!("`{}`", listened.hhmmss())];
+    if let Some(requester) = song.requester() {
+        details.push(format!("<@{}>", requester));
+    }
+    details.push(format!("<t:{}:R>", song.started_at));
+    format!(
+        "{}. {}\n\u{2003}{}",
+        number,
+        title,
+        details.join(" \u{2022} ")
+    )
+}
+
+#[command]
+#[aliases("played", "recent")]
+#[only_in(guilds)]
+#[sub_commands(replay)]
+async fn history(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    run(ctx, &msg.into()).await
+}
+
+/// Show the songs recently played in the guild, the most recent first.
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>) -> CommandResult {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
+
+    let songs = history::recent(guild.id, 0, HISTORY_LIMIT).await?;
+    if songs.is_empty() {
+        return Err(LocalizedError::new("history.empty").into());
+    }
+
+    let lang = invocation.language().await;
+    let title = lang.translate("history.title", json!({ "guild": guild.name }))?;
+    let lines: Vec<String> = songs
+        .iter()
+        .enumerate()
+        .map(|(index, song)| song_line(lang, index + 1, song))
+        .collect();
+    let pages = lines
+        .chunks(PAGE_SIZE)
+        .map(|lines| {
+            let mut e = CreateEmbed::default();
+            invocation.embed_author(&mut e);
+            e.thumbnail(constants::MUSIC_ICON);
+            e.color(Colour::DARK_PURPLE);
+            e.title(&title);
+            e.description(lines.join("\n"));
+            e
+        })
+        .collect();
+
+    Paginator::new(pages)
+        .footer(lang.get("history.replay_hint"))
+        .send(ctx, invocation)
+        .await
+}
+
+#[command]
+#[only_in(guilds)]
+async fn replay(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let number = args
+        .single::<usize>()
+        .map_err(|_| LocalizedError::new("error.history_number"))?;
+    run_replay(ctx, &msg.into(), number).await
+}
+
+/// Queue again a song of the history, by its number in the `history`
+/// command. The most recent song is the first.
+pub async fn run_replay(
+    ctx: &Context, invocation: &Invocation<'_>, number: usize,
+) -> CommandResult {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
+
+    let skip = number
+        .checked_sub(1)
+        .ok_or_else(|| LocalizedError::new("error.history_number"))?;
+    let song = history::recent(guild.id, skip as u64, 1)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| LocalizedError::new("error.history_position"))?;
+    let url = song
+        .url
+        .ok_or_else(|| LocalizedError::new("error.history_url"))?;
+
+    super::play::run(ctx, invocation, &url).await
+}
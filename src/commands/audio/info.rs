@@ -0,0 +1,132 @@
+use serde_json::json;
+use serenity::client::Context;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::channel::Message;
+use serenity::utils::Colour;
+
+use crate::audio::queue;
+use crate::constants::MUSIC_ICON;
+use crate::invocation::Invocation;
+use crate::lang::LocalizedError;
+
+/// Longest part of the description of a track that is shown.
+const DESCRIPTION_EXCERPT: usize = 300;
+
+/// Start of `text` up to `limit` characters, cut at a word when possible.
+fn excerpt(text: &str, limit: usize) -> String {
+    let text = text.trim();
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(limit).collect();
+    let cut = match cut.rfind(char::is_whitespace) {
+        Some(end) => &cut[..end],
+        None => &cut,
+    };
+    format!("{}\u{2026}", cut.trim_end())
+}
+
+#[command]
+#[aliases("track", "track_info")]
+#[only_in(guilds)]
+async fn info(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let position = match args.current() {
+        Some(_) => Some(
+            args.single::<usize>()
+                .map_err(|_| LocalizedError::new("error.position_number"))?,
+        ),
+        None => None,
+    };
+    run(ctx, &msg.into(), position).await
+}
+
+/// Show a track of the queue, the current one when `position` is unset.
+/// Positions start at 1, like in the queue.
+pub async fn run(
+    ctx: &Context, invocation: &Invocation<'_>, position: Option<usize>,
+) -> CommandResult {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
+    let guild_id = guild.id;
+
+    let queues = queue::get_queues().await;
+    let queue = queue::get(&queues, guild_id)
+        .ok_or_else(|| LocalizedError::new("error.no_queue"))?
+        .read()
+        .await;
+    let songs = queue.get_tracks();
+    if songs.is_empty() {
+        return Err(LocalizedError::new("queue.empty").into());
+    }
+    let index = match position {
+        Some(position) => position.checked_sub(1),
+        None => Some(queue.current_index()),
+    };
+    let song = index
+        .and_then(|index| songs.get(index))
+        .cloned()
+        .ok_or_else(|| {
+            LocalizedError::with("error.queue_position", json!({ "max": songs.len() }))
+        })?;
+    let index = index.unwrap_or_default();
+    let total = songs.len();
+    drop(queue);
+    drop(queues);
+
+    let lang = invocation.language().await;
+    let unknown = || lang.get("song.unknown");
+
+    let mut fields = vec![(
+        lang.get("info.position"),
+        format!("{} / {}", index + 1, total),
+        true,
+    )];
+    if let Some(uploader) = song.artist.as_ref().or_else(|| song.uploader.as_ref()) {
+        fields.push((lang.get("info.uploader"), uploader.clone(), true));
+    }
+    let duration = song
+        .length()
+        .map(|duration| lang.format_duration(duration))
+        .unwrap_or_else(|| lang.get("queue.unknown_duration"));
+    fields.push((lang.get("player.field.duration"), duration, true));
+    if let Some(views) = song.view_count {
+        fields.push((
+            lang.get("info.views"),
+            lang.translate("info.view_count", json!({ "count": views }))?,
+            true,
+        ));
+    }
+    if let Some(requester) = song.requester {
+        fields.push((
+            lang.get("info.requester"),
+            format!("<@{}>", requester),
+            true,
+        ));
+    }
+    if let Some(added_at) = song.added_at {
+        fields.push((lang.get("info.added"), format!("<t:{}:R>", added_at), true));
+    }
+
+    invocation
+        .send_embed(ctx, |e| {
+            invocation.embed_author(e);
+            e.color(Colour::DARK_PURPLE);
+            e.title(song.title().unwrap_or_else(unknown));
+            // Some sources only give ids instead of links
+            let url = song.webpage_url.as_ref().or_else(|| song.url.as_ref());
+            if let Some(url) = url.filter(|url| url.starts_with("http")) {
+                e.url(url);
+            }
+            if let Some(description) = song.description.as_deref() {
+                e.description(excerpt(description, DESCRIPTION_EXCERPT));
+            }
+            e.thumbnail(song.thumbnail.as_deref().unwrap_or(MUSIC_ICON));
+            e.fields(fields);
+            e
+        })
+        .await?;
+    Ok(())
+}
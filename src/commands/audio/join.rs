@@ -4,29 +4,37 @@ use serenity::framework::standard::CommandResult;
 use serenity::model::channel::Message;
 
 use crate::audio::queue;
+use crate::invocation::Invocation;
+use crate::lang::LocalizedError;
 use crate::utils::send_info;
 
 #[command]
 #[aliases("j")]
 #[only_in(guilds)]
 #[bucket = "basic"]
-#[description = "Join a voice channel, to be able to play sounds and videos."]
 async fn join(ctx: &Context, msg: &Message) -> CommandResult {
-    let guild = msg.guild(&ctx.cache).await.ok_or("Failed to fetch guild")?;
+    run(ctx, &msg.into()).await
+}
+
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>) -> CommandResult {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
 
     let voice_channel = guild
         .voice_states
-        .get(&msg.author.id)
+        .get(&invocation.author().id)
         .and_then(|voice_state| voice_state.channel_id)
-        .ok_or("Not in a voice channel")?;
+        .ok_or_else(|| LocalizedError::new("error.not_in_voice"))?;
 
     let manager = songbird::get(ctx)
         .await
-        .ok_or("Voice client was not initialized")?
+        .ok_or_else(|| LocalizedError::new("error.voice_client"))?
         .clone();
 
     if manager.get(guild.id).is_some() {
-        return Err("I'm already on a voice channel!".into());
+        return Err(LocalizedError::new("error.already_in_voice").into());
     }
 
     let (call, result) = manager.join(guild.id, voice_channel).await;
@@ -43,16 +51,17 @@ async fn join(ctx: &Context, msg: &Message) -> CommandResult {
     }
 
     let queues = queue::get_queues().await;
-    let queue = queue::get(&queues, guild.id).ok_or("Failed to create queue")?;
+    let queue =
+        queue::get(&queues, guild.id).ok_or_else(|| LocalizedError::new("error.create_queue"))?;
 
-    send_info("voice.update", "voice.joined", msg, ctx).await?;
+    send_info("voice.update", "voice.joined", invocation, ctx).await?;
 
     queue
         .write()
         .await
         .start(
             call.clone(),
-            msg.channel_id,
+            invocation.channel_id(),
             guild.id,
             ctx.http.clone(),
             voice_channel,
@@ -4,20 +4,28 @@ use serenity::framework::standard::CommandResult;
 use serenity::model::channel::Message;
 
 use crate::audio::queue;
+use crate::invocation::Invocation;
+use crate::lang::LocalizedError;
 use crate::utils::send_info;
 
 #[command]
 #[aliases("l", "quit", "exit", "part")]
 #[only_in(guilds)]
 #[bucket = "basic"]
-#[description = "Leave a voice channel."]
 async fn leave(ctx: &Context, msg: &Message) -> CommandResult {
-    let guild = msg.guild(&ctx.cache).await.ok_or("Failed to fetch guild")?;
+    run(ctx, &msg.into()).await
+}
+
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>) -> CommandResult {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
     let guild_id = guild.id;
 
     let manager = songbird::get(ctx)
         .await
-        .ok_or("Voice client was not initialized")?
+        .ok_or_else(|| LocalizedError::new("error.voice_client"))?
         .clone();
 
     let queues = queue::get_queues().await;
@@ -25,10 +33,12 @@ async fn leave(ctx: &Context, msg: &Message) -> CommandResult {
         queue.write().await.clear()?;
     }
 
-    manager.get(guild_id).ok_or("Not in a voice channel")?;
+    manager
+        .get(guild_id)
+        .ok_or_else(|| LocalizedError::new("error.bot_not_in_voice"))?;
     manager.remove(guild_id).await?;
 
-    send_info("voice.update", "voice.left", msg, ctx).await?;
+    send_info("voice.update", "voice.left", invocation, ctx).await?;
 
     Ok(())
 }
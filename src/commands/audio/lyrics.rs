@@ -1,37 +1,279 @@
+use std::collections::HashMap;
+use std::lazy::SyncLazy;
+use std::sync::Arc;
+use std::time::Duration;
+
+use lyrics::{Lyrics, Query, SearchResult, SyncedLyrics};
+use serde_json::json;
+use serenity::builder::CreateEmbed;
 use serenity::client::Context;
 use serenity::framework::standard::macros::command;
 use serenity::framework::standard::{Args, CommandError, CommandResult};
 use serenity::model::channel::Message;
+use serenity::model::id::{GuildId, MessageId};
+use serenity::prelude::Mutex;
+use serenity::utils::Colour;
+use tracing::warn;
 
 use crate::audio::queue;
+use crate::audio::source::MediaResource;
 use crate::constants::{self};
-use crate::paginator::send_pages;
+use crate::invocation::Invocation;
+use crate::lang::{Language, LocalizedError, TranslationError};
+use crate::paginator::{send_pages, split_pages, MAX_DESCRIPTION};
+use crate::{database, LyricsContainer};
+
+/// Interval between playback position checks while following lyrics.
+const LIVE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Live lyrics message currently following playback in every guild. Starting a
+/// new session replaces the previous one.
+static LIVE_SESSIONS: SyncLazy<Mutex<HashMap<GuildId, MessageId>>> =
+    SyncLazy::new(|| Mutex::new(HashMap::new()));
 
-async fn get_current_song(ctx: &Context, msg: &Message) -> Result<String, CommandError> {
-    let guild = msg.guild(&ctx.cache).await.ok_or("Failed to fetch guild")?;
+async fn get_current_song(
+    ctx: &Context, invocation: &Invocation<'_>,
+) -> Result<MediaResource, CommandError> {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
     let guild_id = guild.id;
 
     let queues = queue::get_queues().await;
     let queue = queue::get(&queues, guild_id)
-        .ok_or("No queue found for guild")?
+        .ok_or_else(|| LocalizedError::new("error.no_queue"))?
         .read()
         .await;
-    let current = queue.current().ok_or("Failed to fetch current song")?;
-    let title = current.title().ok_or("Failed to fetch current song")?;
-    Ok(title)
+    let current = queue
+        .current()
+        .ok_or_else(|| LocalizedError::new("error.current_song"))?;
+    Ok(current.clone())
+}
+
+/// Playback position of `track`, or `None` once it is no longer playing.
+async fn get_position(guild_id: GuildId, track: &MediaResource) -> Option<Duration> {
+    let queues = queue::get_queues().await;
+    let queue = queue::get(&queues, guild_id)?.read().await;
+    if queue.current()?.url != track.url {
+        return None;
+    }
+    Some(queue.track_info().await.ok()?.position)
+}
+
+/// Lyrics query from the structured metadata of a track, falling back to its
+/// cleaned video title.
+fn track_query(track: &MediaResource) -> Option<Query> {
+    let query = match &track.track {
+        Some(title) => Query::new(title.as_str()).artist(track.artist.clone().unwrap_or_default()),
+        None => Query::from_video(
+            track.title()?,
+            track.artist.as_deref(),
+            track.uploader.as_deref(),
+        ),
+    };
+    let duration = track
+        .duration
+        .filter(|secs| secs.is_finite() && *secs > 0.0)
+        .map(Duration::from_secs_f64);
+    Some(match duration {
+        Some(duration) => query.duration(duration),
+        None => query,
+    })
+}
+
+/// Search lyrics, going through the database cache first.
+async fn find_lyrics(
+    client: &Lyrics, query: Query, synced: bool,
+) -> Result<SearchResult, lyrics::LyricsError> {
+    let (artist, title) = query.key();
+    if let Some(cached) = database::get_lyrics(&artist, &title).await {
+        if !synced || cached.synced.is_some() {
+            return Ok(cached);
+        }
+    }
+
+    let result = if synced {
+        client.search_synced(query).await?
+    } else {
+        client.search(query).await?
+    };
+    if let Err(err) = database::save_lyrics(artist, title, result.clone()).await {
+        warn!("Failed to cache lyrics: {:?}", err);
+    }
+    Ok(result)
+}
+
+async fn get_client(ctx: &Context) -> Result<Arc<Lyrics>, CommandError> {
+    let client = ctx
+        .data
+        .read()
+        .await
+        .get::<LyricsContainer>()
+        .cloned()
+        .ok_or_else(|| LocalizedError::new("error.lyrics_client"))?;
+    Ok(client)
+}
+
+fn source_credit(lang: Language, lyrics: &SearchResult) -> Result<String, TranslationError> {
+    match &lyrics.source_url {
+        Some(url) => lang.translate(
+            "lyrics.source.url",
+            json!({ "provider": lyrics.provider, "url": url }),
+        ),
+        None => lang.translate("lyrics.source", json!({ "provider": lyrics.provider })),
+    }
+}
+
+fn live_embed<'a>(
+    e: &'a mut CreateEmbed, lyrics: &SearchResult, synced: &SyncedLyrics, index: Option<usize>,
+    footer: &str,
+) -> &'a mut CreateEmbed {
+    let line = |i: usize| {
+        synced
+            .lines()
+            .get(i)
+            .map(|line| line.text.trim())
+            .filter(|text| !text.is_empty())
+            .unwrap_or("\u{266a}")
+    };
+    let (current, next) = match index {
+        Some(i) => (line(i), synced.lines().get(i + 1).map(|_| line(i + 1))),
+        None => ("\u{266a}", synced.lines().first().map(|_| line(0))),
+    };
+
+    e.thumbnail(constants::LYRICS_ICON);
+    e.color(Colour::DARK_PURPLE);
+    e.title(format!("{} - {}", lyrics.artist, lyrics.title));
+    if let Some(url) = &lyrics.source_url {
+        e.url(url);
+    }
+    e.description(format!("**{}**\n{}", current, next.unwrap_or_default()));
+    e.footer(|f| f.text(footer));
+    e
 }
 
 #[command]
 #[only_in(guilds)]
+#[sub_commands(live)]
 async fn lyrics(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let name = match args.remains() {
-        Some(t) => Some(t.to_string()),
-        None => get_current_song(ctx, msg).await.ok(),
+    run(ctx, &msg.into(), args.remains()).await
+}
+
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>, query: Option<&str>) -> CommandResult {
+    let query = match query {
+        Some(t) => Some(Query::from(t)),
+        None => get_current_song(ctx, invocation)
+            .await
+            .ok()
+            .as_ref()
+            .and_then(track_query),
     };
-    let name = name.ok_or("You must provide a song name to search!")?;
-    let lyrics = lyrics::search(name).await.map_err(|_| "Song not found!")?;
-    let lines: Vec<&str> = lyrics.lyrics.lines().collect();
-    let text = lines.chunks(30).map(|lines| lines.join("\n")).collect();
+    let query = query.ok_or_else(|| LocalizedError::new("error.lyrics_query"))?;
+
+    let client = get_client(ctx).await?;
+    let lyrics = find_lyrics(&client, query, false)
+        .await
+        .map_err(|_| LocalizedError::new("error.lyrics_not_found"))?;
+
+    let lang = invocation.language().await;
+    let source = source_credit(lang, &lyrics)?;
+
+    // The credit goes after a blank line, on the last page
+    let lines = lyrics.lyrics.lines().chain(["", source.as_str()]);
+    let text = split_pages(lines, MAX_DESCRIPTION);
+
+    let title = format!("{} - {}", lyrics.artist, lyrics.title);
+    send_pages(title, text, constants::LYRICS_ICON.into(), ctx, invocation).await
+}
+
+#[command]
+#[only_in(guilds)]
+async fn live(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    run_live(ctx, &msg.into()).await
+}
+
+pub async fn run_live(ctx: &Context, invocation: &Invocation<'_>) -> CommandResult {
+    let guild_id = invocation
+        .guild_id()
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
+    let track = get_current_song(ctx, invocation).await?;
+    let query = track_query(&track).ok_or_else(|| LocalizedError::new("error.current_song"))?;
+
+    let client = get_client(ctx).await?;
+    let lyrics = find_lyrics(&client, query, true)
+        .await
+        .map_err(|_| LocalizedError::new("error.synced_lyrics"))?;
+    let synced = lyrics
+        .synced
+        .clone()
+        .ok_or_else(|| LocalizedError::new("error.synced_lyrics"))?;
+
+    let lang = invocation.language().await;
+    let footer = lang.translate("lyrics.source", json!({ "provider": lyrics.provider }))?;
+
+    let mut index = get_position(guild_id, &track)
+        .await
+        .and_then(|position| synced.index_at(position));
+    let mut live_msg = invocation
+        .send_embed(ctx, |e| live_embed(e, &lyrics, &synced, index, &footer))
+        .await?;
+    LIVE_SESSIONS.lock().await.insert(guild_id, live_msg.id);
+
+    // Polling the position keeps the message in sync after seeking. Nothing in
+    // the loop returns early, so the session is always removed afterwards.
+    let ended = loop {
+        tokio::time::sleep(LIVE_POLL_INTERVAL).await;
+
+        if LIVE_SESSIONS.lock().await.get(&guild_id) != Some(&live_msg.id) {
+            break false;
+        }
+        let position = match get_position(guild_id, &track).await {
+            Some(position) => position,
+            None => break true,
+        };
+
+        let current = synced.index_at(position);
+        if current == index {
+            continue;
+        }
+        index = current;
+        let edited = live_msg
+            .edit(ctx, |m| {
+                m.embed(|e| live_embed(e, &lyrics, &synced, index, &footer));
+                m
+            })
+            .await;
+        // The message was most likely deleted, which ends the session
+        if let Err(err) = edited {
+            warn!("Failed to update live lyrics: {:?}", err);
+            break false;
+        }
+    };
+
+    {
+        let mut sessions = LIVE_SESSIONS.lock().await;
+        if sessions.get(&guild_id) == Some(&live_msg.id) {
+            sessions.remove(&guild_id);
+        }
+    }
+    if !ended {
+        return Ok(());
+    }
 
-    send_pages(lyrics.title, text, constants::LYRICS_ICON.into(), ctx, msg).await
+    let finished = lang.get("lyrics.live.finished");
+    live_msg
+        .edit(ctx, |m| {
+            m.embed(|e| {
+                e.thumbnail(constants::LYRICS_ICON);
+                e.color(Colour::DARK_PURPLE);
+                e.title(format!("{} - {}", lyrics.artist, lyrics.title));
+                e.description(finished);
+                e.footer(|f| f.text(&footer));
+                e
+            });
+            m
+        })
+        .await?;
+    Ok(())
 }
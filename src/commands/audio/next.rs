@@ -6,25 +6,50 @@ use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::channel::Message;
 
 use crate::audio::queue;
+use crate::checks::{self, SkipVote, LISTENER_CHECK};
+use crate::invocation::Invocation;
+use crate::lang::LocalizedError;
 use crate::utils::send_translated_info;
 
 #[command]
 #[only_in(guilds)]
+#[checks(Listener)]
 #[aliases("skip")]
 async fn next(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
-    let guild = msg.guild(&ctx.cache).await.ok_or("Failed to fetch guild")?;
+    run(ctx, &msg.into()).await
+}
+
+/// Skip the current song, or vote to skip it when the author is not a DJ.
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>) -> CommandResult {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
     let guild_id = guild.id;
 
+    let vote = checks::vote_skip(ctx, guild_id, invocation.author().id).await?;
+    if let SkipVote::Pending { votes, needed } = vote {
+        return send_translated_info(
+            "voice.update",
+            "queue.skip_votes",
+            json!({ "votes": votes, "needed": needed }),
+            invocation,
+            ctx,
+        )
+        .await;
+    }
+
     try_play_all(guild_id, true).await?;
 
     let queues = queue::get_queues().await;
-    let queue = queue::get(&queues, guild_id).ok_or("There are no active queue for this server")?;
+    let queue =
+        queue::get(&queues, guild_id).ok_or_else(|| LocalizedError::new("error.no_queue"))?;
 
     send_translated_info(
         "voice.update",
         "queue.next",
         json!({"remaining": queue.read().await.remaining().len()}),
-        msg,
+        invocation,
         ctx,
     )
     .await
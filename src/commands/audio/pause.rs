@@ -4,21 +4,31 @@ use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::channel::Message;
 
 use crate::audio::queue;
+use crate::checks::PLAYER_CHECK;
+use crate::invocation::Invocation;
+use crate::lang::LocalizedError;
 use crate::utils::send_info;
 
 #[command]
 #[only_in(guilds)]
-#[description = "Pause the current playing sound"]
+#[checks(Player)]
 async fn pause(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
-    let guild = msg.guild(&ctx.cache).await.ok_or("Failed to fetch guild")?;
+    run(ctx, &msg.into()).await
+}
+
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>) -> CommandResult {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
     let guild_id = guild.id;
 
     let queues = queue::get_queues().await;
     let mut queue = queue::get(&queues, guild_id)
-        .ok_or("No queue found for guild")?
+        .ok_or_else(|| LocalizedError::new("error.no_queue"))?
         .write()
         .await;
     queue.pause()?;
 
-    send_info("voice.update", "queue.pause", msg, ctx).await
+    send_info("voice.update", "queue.pause", invocation, ctx).await
 }
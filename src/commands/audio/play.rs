@@ -1,27 +1,58 @@
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::error::Error;
 use std::lazy::SyncLazy;
+use std::time::Duration;
 
 use futures::stream::{self, StreamExt};
 use queue::try_play_all;
 use regex::Regex;
+use serde_json::json;
 use serenity::client::Context;
 use serenity::framework::standard::macros::command;
-use serenity::framework::standard::{Args, CommandResult};
-use serenity::model::channel::Message;
+use serenity::framework::standard::{Args, CommandError, CommandResult};
+use serenity::model::channel::{Message, Reaction, ReactionType};
+use serenity::model::id::{GuildId, MessageId};
+use serenity::prelude::{Mutex, RwLock};
 use serenity::utils::Colour;
+use tracing::warn;
 
-use crate::audio::source::{ytdl_metadata, MediaResource};
-use crate::audio::{queue, spotify};
+use crate::audio::source::{ytdl_metadata, MediaResource, SearchSource};
+use crate::audio::{dashboard, queue, spotify, ytmusic};
+use crate::checks::{can_use_channel, RATE_LIMITED};
 use crate::constants::MUSIC_ICON;
+use crate::hooks::report_error;
+use crate::invocation::Invocation;
+use crate::lang::LocalizedError;
+use crate::prefixes::{self, find_prefix};
+use crate::{database, ratelimit};
 
-async fn get_videos<S>(query: S) -> Result<Vec<MediaResource>, Box<dyn Error>>
+/// How long errors of song requests stay in the request channel.
+const REQUEST_ERROR_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Messages remembered as played with the play reaction.
+const MAX_PLAYED_MESSAGES: usize = 1_000;
+
+/// Play reaction of every guild seen, so reactions are not checked against
+/// the database. The reaction is only changed through the `play_reaction`
+/// setting, which calls [`forget_play_reaction`].
+static PLAY_REACTIONS: SyncLazy<RwLock<HashMap<GuildId, Option<ReactionType>>>> =
+    SyncLazy::new(|| RwLock::new(HashMap::new()));
+
+/// Latest messages whose links were queued with the play reaction, so
+/// reacting again doesn't queue them twice.
+static PLAYED_MESSAGES: SyncLazy<Mutex<VecDeque<MessageId>>> =
+    SyncLazy::new(|| Mutex::new(VecDeque::new()));
+
+static RE: SyncLazy<Regex> = SyncLazy::new(|| {
+    let regex = r"https?://(www\.)?[-a-zA-Z0-9@:%._\+~#=]{1,256}\.[a-zA-Z0-9()]{1,6}\b([-a-zA-Z0-9()@:%_\+.~#?&//=]*)";
+    Regex::new(regex).expect("Failed to compile URL regex")
+});
+
+async fn get_videos<S>(
+    query: S, source: SearchSource,
+) -> Result<Vec<MediaResource>, Box<dyn Error>>
 where
     S: AsRef<str>, {
-    static RE: SyncLazy<Regex> = SyncLazy::new(|| {
-        let regex = r"https?://(www\.)?[-a-zA-Z0-9@:%._\+~#=]{1,256}\.[a-zA-Z0-9()]{1,6}\b([-a-zA-Z0-9()@:%_\+.~#?&//=]*)";
-        Regex::new(regex).expect("Failed to compile URL regex")
-    });
-
     // Check if is a Spotify uri
     if spotify::is_spotify_url(query.as_ref()) {
         let titles = spotify::get_titles(query).await?;
@@ -33,6 +64,17 @@ where
         return Ok(tracks);
     }
 
+    // Check if is a YouTube Music album, playlist or artist uri
+    if ytmusic::is_youtube_music_url(query.as_ref()) {
+        let ids = ytmusic::get_video_ids(query).await?;
+        let tracks = stream::iter(ids)
+            .map(MediaResource::with_video_id)
+            .filter_map(async move |v| v.await.ok())
+            .collect::<Vec<MediaResource>>()
+            .await;
+        return Ok(tracks);
+    }
+
     // Check if is a normal uri
     if RE.is_match(query.as_ref()) {
         return Ok(ytdl_metadata(query)
@@ -40,37 +82,278 @@ where
             .map_err(|err| format!("`youtube-dl` error {:?}", err))?);
     }
 
-    let query = MediaResource::with_query(query).await?;
+    let query = MediaResource::search(query, source).await?;
     Ok(vec![query])
 }
 
+/// Links of a text that can be played, web links and Spotify URIs, without
+/// repeating them.
+fn media_links(text: &str) -> Vec<&str> {
+    let spotify_uris = text
+        .split_whitespace()
+        .filter(|word| word.starts_with("spotify:") && spotify::is_spotify_url(word));
+    let mut seen = HashSet::new();
+    RE.find_iter(text)
+        .map(|link| link.as_str())
+        .chain(spotify_uris)
+        .filter(|link| seen.insert(*link))
+        .collect()
+}
+
+/// Whether two reactions use the same emoji. Custom emoji are compared by id,
+/// as they can be renamed.
+fn same_emoji(a: &ReactionType, b: &ReactionType) -> bool {
+    match (a, b) {
+        (ReactionType::Custom { id: a, .. }, ReactionType::Custom { id: b, .. }) => a == b,
+        // The emoji variation selector is optional
+        (ReactionType::Unicode(a), ReactionType::Unicode(b)) => {
+            a.trim_end_matches('\u{fe0f}') == b.trim_end_matches('\u{fe0f}')
+        },
+        _ => false,
+    }
+}
+
+/// Keep the errors meant for users, so they are shown translated.
+fn query_error(err: Box<dyn Error>) -> CommandError {
+    match err.downcast::<LocalizedError>() {
+        Ok(err) => CommandError::from(*err),
+        Err(err) => format!("Failed to query: {:?}", err).into(),
+    }
+}
+
 #[command]
 #[aliases("p")]
 #[only_in(guilds)]
 #[bucket = "basic"]
-#[description = "Play a sound, song, playlist, or album with the provided URL or YouTube query. \
-                 Supported websites are: YouTube, Spotify, Soundcloud, and 200+ more websites."]
 async fn play(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let query = args
-        .remains()
-        .ok_or("Must provide a URL to a video or audio, or a search query.")?;
-    let audios = get_videos(&query)
+    match (args.remains(), &msg.referenced_message) {
+        (Some(query), _) => run(ctx, &msg.into(), query).await,
+        // Replying to a message plays its links
+        (None, Some(replied)) => run_links(ctx, &msg.into(), &replied.content).await,
+        (None, None) => Err(LocalizedError::new("error.play_query").into()),
+    }
+}
+
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>, query: &str) -> CommandResult {
+    play_queries(ctx, invocation, &[query]).await
+}
+
+/// Play every media link of a text, like the content of a message.
+pub async fn run_links(ctx: &Context, invocation: &Invocation<'_>, text: &str) -> CommandResult {
+    let links = media_links(text);
+    if links.is_empty() {
+        return Err(LocalizedError::new("error.no_links").into());
+    }
+    play_queries(ctx, invocation, &links).await
+}
+
+/// Queue the songs of every query and show them. Queries that fail are
+/// skipped, their error is only shown when every query failed.
+async fn play_queries(
+    ctx: &Context, invocation: &Invocation<'_>, queries: &[&str],
+) -> CommandResult {
+    let mut audios = Vec::new();
+    let mut error = None;
+    for query in queries {
+        match enqueue(ctx, invocation, query).await {
+            Ok(added) => audios.extend(added),
+            Err(err) => {
+                error.get_or_insert(err);
+            },
+        }
+    }
+    if audios.is_empty() {
+        return Err(error.unwrap_or_else(|| LocalizedError::new("error.no_results").into()));
+    }
+    let lang = invocation.language().await;
+
+    let mut audio_list = audios
+        .iter()
+        .take(10)
+        .filter_map(|youtube| Some(format!("\u{279c} {}", youtube.title()?)))
+        .collect::<Vec<String>>()
+        .join("\n");
+    if audio_list.is_empty() {
+        audio_list = format!("\u{279c} {}", lang.get("queue.titles_hidden"));
+    }
+
+    // Show embed for every audio
+    let added = lang.translate("queue.added", json!({ "count": audios.len() }))?;
+    invocation
+        .send_embed(ctx, |e| {
+            invocation.embed_author(e);
+            e.thumbnail(MUSIC_ICON);
+            e.color(Colour::DARK_PURPLE);
+            e.title(lang.get("queue.updated"));
+            e.field(added, audio_list, true);
+            e
+        })
+        .await?;
+
+    start_playing(invocation).await
+}
+
+/// Play a message sent to the request channel of its guild, then delete it
+/// to leave only the dashboard in the channel. Errors are shown for a few
+/// seconds. Requests count towards the `basic` rate limit, like the `play`
+/// command.
+pub async fn handle_request(ctx: &Context, msg: &Message) {
+    let guild_id = match msg.guild_id {
+        Some(guild_id) if !msg.author.bot => guild_id,
+        _ => return,
+    };
+    let request_channel = dashboard::request_channel(guild_id).await;
+    if request_channel != Some(msg.channel_id) || is_command(ctx, guild_id, msg).await {
+        return;
+    }
+
+    let query = msg.content.trim();
+    if query.is_empty() {
+        return;
+    }
+
+    let invocation = Invocation::from(msg);
+    let result = match ratelimit::BASIC.hit(msg.author.id).await {
+        Ok(()) => match enqueue(ctx, &invocation, query).await {
+            Ok(_) => start_playing(&invocation).await,
+            Err(err) => Err(err),
+        },
+        Err(_) => Err(LocalizedError::new(RATE_LIMITED).into()),
+    };
+    if let Err(why) = result {
+        if let Some(reply) = report_error(ctx, &invocation, "play", why).await {
+            let http = ctx.http.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(REQUEST_ERROR_TIMEOUT).await;
+                if let Err(err) = reply.delete(&http).await {
+                    warn!("Failed to delete request error: {:?}", err);
+                }
+            });
+        }
+    }
+
+    if let Err(err) = msg.delete(ctx).await {
+        warn!("Failed to delete song request: {:?}", err);
+    }
+}
+
+/// Play reaction of a guild, if it has one.
+async fn play_reaction(guild_id: GuildId) -> Option<ReactionType> {
+    if let Some(emoji) = PLAY_REACTIONS.read().await.get(&guild_id) {
+        return emoji.clone();
+    }
+
+    let emoji = database::get_guild(guild_id)
+        .await
+        .and_then(|guild| guild.play_reaction)
+        .and_then(|emoji| emoji.parse::<ReactionType>().ok());
+    PLAY_REACTIONS.write().await.insert(guild_id, emoji.clone());
+    emoji
+}
+
+/// Read the play reaction of a guild from the database again after it
+/// changed.
+pub async fn forget_play_reaction(guild_id: GuildId) {
+    PLAY_REACTIONS.write().await.remove(&guild_id);
+}
+
+/// Remember a message as played, unless it already was. Returns whether it
+/// was not.
+async fn mark_played(message_id: MessageId) -> bool {
+    let mut played = PLAYED_MESSAGES.lock().await;
+    if played.contains(&message_id) {
+        return false;
+    }
+    if played.len() >= MAX_PLAYED_MESSAGES {
+        played.pop_front();
+    }
+    played.push_back(message_id);
+    true
+}
+
+async fn unmark_played(message_id: MessageId) {
+    PLAYED_MESSAGES.lock().await.retain(|id| *id != message_id);
+}
+
+/// Play the links of a message when someone reacts to it with the play
+/// reaction of the guild. Messages are only played once, and reactions count
+/// towards the `basic` rate limit, like the `play` command.
+pub async fn handle_reaction(ctx: &Context, reaction: &Reaction) {
+    let guild_id = match reaction.guild_id {
+        Some(guild_id) => guild_id,
+        None => return,
+    };
+    let emoji = play_reaction(guild_id).await;
+    let matches = emoji.map_or(false, |emoji| same_emoji(&emoji, &reaction.emoji));
+    if !matches
+        || can_use_channel(guild_id, reaction.channel_id)
+            .await
+            .is_err()
+    {
+        return;
+    }
+
+    let user = match reaction.user(ctx).await {
+        Ok(user) if !user.bot => user,
+        Ok(_) => return,
+        Err(err) => return warn!("Failed to get the user of a reaction: {:?}", err),
+    };
+    if !mark_played(reaction.message_id).await {
+        return;
+    }
+    let message = match reaction.message(ctx).await {
+        Ok(message) => message,
+        Err(err) => {
+            unmark_played(reaction.message_id).await;
+            return warn!("Failed to get the message of a reaction: {:?}", err);
+        },
+    };
+
+    let invocation = Invocation::Reaction(&message, &user);
+    let result = match ratelimit::BASIC.hit(user.id).await {
+        Ok(()) => run_links(ctx, &invocation, &message.content).await,
+        Err(_) => Err(LocalizedError::new(RATE_LIMITED).into()),
+    };
+    if let Err(why) = result {
+        // Let the message be played again once the error is solved
+        unmark_played(message.id).await;
+        report_error(ctx, &invocation, "play", why).await;
+    }
+}
+
+/// Whether a message is meant for the command framework instead.
+async fn is_command(ctx: &Context, guild_id: GuildId, msg: &Message) -> bool {
+    let prefixes = prefixes::get_prefixes(guild_id).await;
+    let mentioned = msg.mentions_user_id(ctx.cache.current_user_id().await);
+    mentioned || find_prefix(&prefixes, &msg.content).is_some()
+}
+
+/// Search `query` and add the results to the queue of the guild, joining the
+/// voice channel of the author first when needed. Returns the added songs.
+async fn enqueue(
+    ctx: &Context, invocation: &Invocation<'_>, query: &str,
+) -> Result<Vec<MediaResource>, CommandError> {
+    let source = database::get_user(invocation.author().id)
         .await
-        .map_err(|err| format!("Failed to query: {:?}", err))?;
-    let audio_len = audios.len();
+        .map(|user| user.search_source)
+        .unwrap_or_default();
+    let mut audios = get_videos(&query, source).await.map_err(query_error)?;
 
-    let guild = msg.guild(&ctx.cache).await.ok_or("Failed to fetch guild")?;
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
 
     let manager = songbird::get(ctx)
         .await
-        .ok_or("Voice client was not initialized")?
+        .ok_or_else(|| LocalizedError::new("error.voice_client"))?
         .clone();
 
     let voice_channel = guild
         .voice_states
-        .get(&msg.author.id)
+        .get(&invocation.author().id)
         .and_then(|voice_state| voice_state.channel_id)
-        .ok_or("Not in a voice channel")?;
+        .ok_or_else(|| LocalizedError::new("error.not_in_voice"))?;
 
     {
         let mut queues = queue::get_queues_mut().await;
@@ -78,11 +361,12 @@ async fn play(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     }
 
     let queues = queue::get_queues().await;
-    let queue = queue::get(&queues, guild.id).ok_or("Failed to create queue")?;
+    let queue =
+        queue::get(&queues, guild.id).ok_or_else(|| LocalizedError::new("error.create_queue"))?;
 
     if let Some(vc) = queue.read().await.voice_channel() {
         if voice_channel != vc {
-            return Err("You are not in the current bot voice channel.".into());
+            return Err(LocalizedError::new("error.not_in_bot_channel").into());
         }
     }
 
@@ -98,7 +382,7 @@ async fn play(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
             .await
             .start(
                 call.clone(),
-                msg.channel_id,
+                invocation.channel_id(),
                 guild.id,
                 ctx.http.clone(),
                 voice_channel,
@@ -117,58 +401,51 @@ async fn play(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     }
 
     // Early exit if no videos found
-    if audio_len == 0 {
-        return Err("No search results found.".into());
+    if audios.is_empty() {
+        return Err(LocalizedError::new("error.no_results").into());
     }
 
-    let mut audio_list = audios
-        .iter()
-        .take(10)
-        .filter_map(|youtube| Some(format!("\u{279c} {}", youtube.title()?)))
-        .collect::<Vec<String>>()
-        .join("\n");
-    if audio_list.is_empty() {
-        audio_list = "\u{279c} (Titles not displayed)".to_string();
+    // Apply the queue limits of the guild
+    if let Some(settings) = database::get_guild(guild.id).await {
+        if let Some(max) = settings.max_track_duration {
+            audios.retain(|audio| audio.duration.map_or(true, |secs| secs <= max as f64));
+            if audios.is_empty() {
+                return Err(LocalizedError::with(
+                    "error.track_too_long",
+                    json!({ "duration": max }),
+                )
+                .into());
+            }
+        }
+        if let Some(max) = settings.max_queue_length {
+            let free = (max as usize).saturating_sub(queue.read().await.remaining().len());
+            if free == 0 {
+                return Err(LocalizedError::new("error.queue_full").into());
+            }
+            audios.truncate(free);
+        }
     }
 
     // Add all audios to the queue
-    for audio in audios {
-        queue.write().await.add(audio);
+    for audio in &mut audios {
+        audio.requester = Some(invocation.author().id);
+        audio.added_at = Some(database::timestamp());
+        queue.write().await.add(audio.clone());
     }
+    Ok(audios)
+}
 
-    let text_channel = msg.channel_id;
-
-    // Show embed for every audio
-    text_channel
-        .send_message(&ctx.http, |m| {
-            m.reference_message(msg);
-            m.allowed_mentions(|f| f.replied_user(false));
-            m.embed(|e| {
-                e.author(|a| {
-                    a.name(&msg.author.name);
-                    a.icon_url(
-                        msg.author
-                            .avatar_url()
-                            .unwrap_or_else(|| msg.author.default_avatar_url()),
-                    );
-                    a
-                });
-                e.thumbnail(MUSIC_ICON);
-                e.color(Colour::DARK_PURPLE);
-                e.title("Updated queue");
-                e.field(
-                    format!("Added {} elements to the queue", audio_len),
-                    audio_list,
-                    true,
-                );
-                e
-            });
-            m
-        })
-        .await?;
+/// Start playing the queue of the guild, unless it is already playing.
+async fn start_playing(invocation: &Invocation<'_>) -> CommandResult {
+    let guild_id = invocation
+        .guild_id()
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
+    let queues = queue::get_queues().await;
+    let queue =
+        queue::get(&queues, guild_id).ok_or_else(|| LocalizedError::new("error.create_queue"))?;
 
     if !queue.read().await.is_playing() {
-        try_play_all(guild.id, false).await?;
+        try_play_all(guild_id, false).await?;
     }
     Ok(())
 }
@@ -1,58 +1,160 @@
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use hhmmss::Hhmmss;
 use serde_json::json;
+use serenity::builder::CreateEmbed;
 use serenity::client::Context;
 use serenity::framework::standard::macros::command;
 use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::channel::Message;
+use serenity::utils::Colour;
 
 use crate::audio::queue;
 use crate::audio::source::MediaResource;
 use crate::constants;
-use crate::database::get_language;
-use crate::paginator::send_pages;
+use crate::invocation::Invocation;
+use crate::lang::{Language, LocalizedError};
+use crate::paginator::Paginator;
+
+/// Songs listed on every page of the queue.
+const PAGE_SIZE: usize = 10;
+
+/// Where a song is in the queue.
+enum Status {
+    Played,
+    Playing,
+    /// Waiting to play, in the given time when it is known.
+    Upcoming(Option<Duration>),
+}
+
+/// Line of a song in the queue, with its duration, requester and when it
+/// plays. The playing song is highlighted.
+fn song_line(lang: Language, number: usize, song: &MediaResource, status: Status) -> String {
+    let title = song.title().unwrap_or_else(|| lang.get("song.unknown"));
+    let mut details = vec![match song.length() {
+        Some(duration) => format!("`{}`", duration.hhmmss()),
+        None => lang.get("queue.unknown_duration"),
+    }];
+    if let Some(requester) = song.requester {
+        details.push(format!("<@{}>", requester));
+    }
+    match status {
+        Status::Played => {},
+        Status::Playing => details.push(lang.get("queue.playing")),
+        Status::Upcoming(Some(eta)) => details.push(
+            lang.translate("queue.eta", json!({ "time": lang.format_duration(eta) }))
+                .unwrap_or_default(),
+        ),
+        Status::Upcoming(None) => details.push(lang.get("queue.eta_unknown")),
+    }
+
+    let details = details.join(" \u{2022} ");
+    match status {
+        Status::Playing => format!("**\u{25b6} {}. {}**\n\u{2003}{}", number, title, details),
+        _ => format!("{}. {}\n\u{2003}{}", number, title, details),
+    }
+}
 
 #[command]
 #[aliases("q")]
 #[only_in(guilds)]
-#[description = "Get the music queue and the current playing song"]
 async fn queue(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
-    let guild = msg.guild(ctx).await.ok_or("Failed to fetch guild")?;
+    run(ctx, &msg.into()).await
+}
+
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>) -> CommandResult {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
     let guild_id = guild.id;
 
-    let lang = get_language(msg.author.id, msg.guild_id).await;
-    let empty = lang.get("queue.empty");
+    let lang = invocation.language().await;
 
     let queues = queue::get_queues().await;
     let queue = queue::get(&queues, guild_id)
-        .ok_or("No queue found for guild")?
+        .ok_or_else(|| LocalizedError::new("error.no_queue"))?
         .read()
         .await;
-    let queue: Vec<String> = queue
-        .get_tracks()
-        .iter()
-        .filter_map(MediaResource::title)
-        .enumerate()
-        .map(|(index, title)| format!("{}. {}\n", index + 1, title))
-        .collect();
+    let songs = queue.get_tracks();
+    if songs.is_empty() {
+        return Err(LocalizedError::new("queue.empty").into());
+    }
+    let current = queue.current_index();
+    let position = match queue.track_info().await {
+        Ok(state) => state.position,
+        Err(_) => Duration::default(),
+    };
+
+    // Time until every song plays, unknown after a song of unknown length
+    let mut eta = Some(Duration::default());
+    let mut known = Duration::default();
+    let mut unknown = 0;
+    let mut lines = Vec::with_capacity(songs.len());
+    for (index, song) in songs.iter().enumerate() {
+        let duration = song.length();
+        let status = match index.cmp(&current) {
+            Ordering::Less => Status::Played,
+            Ordering::Equal => Status::Playing,
+            Ordering::Greater => Status::Upcoming(eta),
+        };
+        lines.push(song_line(lang, index + 1, song, status));
+        if index < current {
+            continue;
+        }
 
-    if queue.is_empty() {
-        return Err(empty.into());
+        let left = match duration {
+            Some(duration) if index == current => Some(duration.saturating_sub(position)),
+            duration => duration,
+        };
+        match left {
+            Some(left) => known += left,
+            None => unknown += 1,
+        }
+        eta = eta.zip(left).map(|(eta, left)| eta + left);
+    }
+
+    let mut footer = vec![if unknown == 0 {
+        lang.translate(
+            "queue.total",
+            json!({ "time": lang.format_duration(known) }),
+        )?
+    } else {
+        lang.translate(
+            "queue.total_unknown",
+            json!({ "time": lang.format_duration(known), "count": unknown }),
+        )?
+    }];
+    if queue.repeat() {
+        footer.push(lang.get("player.loop"));
+    }
+    if queue.shuffled() {
+        footer.push(lang.get("queue.shuffled_state"));
     }
 
     let title = lang.translate("queue.title", json!({"guild": guild.name}))?;
-    let queued_songs = lang.translate("queue.songs", json!({"total": queue.len()}))?;
-
-    let pages = queue
-        .chunks(5)
-        .map(|titles| {
-            let mut out = String::from("\n");
-            out.push_str(&queued_songs);
-            out.push('\n');
-            out.push_str("```rust\n");
-            out.push_str(&titles.join("\n"));
-            out.push_str("```");
-            out
+    let queued_songs = lang.translate("queue.songs", json!({"total": songs.len()}))?;
+    let pages = lines
+        .chunks(PAGE_SIZE)
+        .map(|lines| {
+            let mut e = CreateEmbed::default();
+            invocation.embed_author(&mut e);
+            e.thumbnail(constants::MUSIC_ICON);
+            e.color(Colour::DARK_PURPLE);
+            e.title(&title);
+            e.description(format!("{}\n\n{}", queued_songs, lines.join("\n")));
+            e
         })
         .collect();
 
-    send_pages(title, pages, constants::MUSIC_ICON.into(), ctx, msg).await
+    // Release the queue while the pages are shown
+    drop(queue);
+    drop(queues);
+
+    Paginator::new(pages)
+        .footer(footer.join(" \u{2022} "))
+        .start(current / PAGE_SIZE)
+        .send(ctx, invocation)
+        .await
 }
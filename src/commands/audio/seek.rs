@@ -1,5 +1,6 @@
+use std::time::Duration;
+
 use hhmmss::Hhmmss;
-use humantime::parse_duration;
 use serde_json::json;
 use serenity::client::Context;
 use serenity::framework::standard::macros::command;
@@ -7,24 +8,32 @@ use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::channel::Message;
 
 use crate::audio::queue;
-use crate::utils::send_translated_info;
+use crate::invocation::Invocation;
+use crate::lang::LocalizedError;
+use crate::utils::{parse_duration, send_translated_info};
 
 #[command]
 #[only_in(guilds)]
-#[description = "Seek a portion of an audio.\n**Example:** `seek 1min 30s`"]
 async fn seek(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let guild = msg.guild(&ctx.cache).await.ok_or("Failed to fetch guild")?;
+    let position = args
+        .remains()
+        .ok_or_else(|| LocalizedError::new("error.seek_position"))?;
+    run(ctx, &msg.into(), parse_duration(position)?).await
+}
+
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>, position: Duration) -> CommandResult {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
     let guild_id = guild.id;
 
     let queues = queue::get_queues().await;
     let mut queue = queue::get(&queues, guild_id)
-        .ok_or("No queue found for guild")?
+        .ok_or_else(|| LocalizedError::new("error.no_queue"))?
         .write()
         .await;
-    let position = args
-        .remains()
-        .ok_or("You must provide a position to seek")?;
-    queue.seek(parse_duration(position)?)?;
+    queue.seek(position)?;
 
     let position = queue.track_info().await?.position;
 
@@ -32,7 +41,7 @@ async fn seek(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
         "voice.update",
         "audio.seek",
         json!({ "position": position.hhmmss() }),
-        msg,
+        invocation,
         ctx,
     )
     .await
@@ -5,33 +5,41 @@ use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::channel::Message;
 
 use crate::audio::queue;
+use crate::invocation::Invocation;
+use crate::lang::LocalizedError;
 use crate::utils::send_translated_info;
 
 #[command]
 #[only_in(guilds)]
 async fn volume(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let guild = msg.guild(&ctx.cache).await.ok_or("Failed to fetch guild")?;
-    let guild_id = guild.id;
+    let volume = args
+        .single::<u8>()
+        .map_err(|_| LocalizedError::new("error.volume"))?;
+    run(ctx, &msg.into(), volume).await
+}
 
-    let volume = args.single::<u8>()?;
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>, volume: u8) -> CommandResult {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
+    let guild_id = guild.id;
 
     if !(0..=200).contains(&volume) {
-        return Err("Volume must be in range from 0 to 200".into());
+        return Err(LocalizedError::new("error.volume").into());
     }
 
     let queues = queue::get_queues().await;
     let mut queue = queue::get(&queues, guild_id)
-        .ok_or("No queue found for guild")?
+        .ok_or_else(|| LocalizedError::new("error.no_queue"))?
         .write()
         .await;
     queue.volume(f32::from(volume) / 100.0)?;
-    msg.reply(ctx, format!("Set volume to {}%", volume)).await?;
-
     send_translated_info(
         "voice.update",
         "queue.volume",
         json!({ "volume": volume }),
-        msg,
+        invocation,
         ctx,
     )
     .await
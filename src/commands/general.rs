@@ -1,13 +1,21 @@
 use serenity::framework::standard::macros::group;
 
-mod guild_settings;
-mod latency;
-mod settings;
+pub(super) mod guild_settings;
+pub(super) mod latency;
+pub(super) mod prefix;
+pub(super) mod reload_translations;
+pub(super) mod settings;
+pub(super) mod stats;
 
 use self::guild_settings::GUILD_SETTINGS_COMMAND;
 use self::latency::LATENCY_COMMAND;
+use self::prefix::PREFIX_COMMAND;
+use self::reload_translations::RELOAD_TRANSLATIONS_COMMAND;
 use self::settings::SETTINGS_COMMAND;
+use self::stats::STATS_COMMAND;
+use crate::checks::RATE_LIMIT_CHECK;
 
 #[group]
-#[commands(settings, guild_settings, latency)]
+#[commands(settings, guild_settings, prefix, latency, stats, reload_translations)]
+#[checks(RateLimit)]
 struct General;
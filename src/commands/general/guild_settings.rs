@@ -1,13 +1,430 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use serde_json::json;
 use serenity::client::Context;
 use serenity::framework::standard::macros::command;
-use serenity::framework::standard::CommandResult;
-use serenity::model::channel::Message;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::channel::{ChannelType, Message, ReactionType};
+use serenity::model::guild::Guild as DiscordGuild;
+use serenity::model::id::{ChannelId, GuildId, RoleId};
+use serenity::utils::{parse_channel, parse_role, Colour};
+use tracing::warn;
+
+use crate::audio::dashboard;
+use crate::commands::play;
+use crate::database;
+use crate::database::guild::Guild;
+use crate::invocation::Invocation;
+use crate::lang::{Language, LocalizedError};
+use crate::prefixes::{self, get_prefixes};
+use crate::utils::parse_duration;
+
+/// Value that resets a setting to its default.
+const RESET: &[&str] = &["none", "reset", "default"];
+
+/// Longest queue that can be configured.
+const MAX_QUEUE_LENGTH: u32 = 10_000;
+
+/// Shortest track duration limit that can be configured.
+const MIN_TRACK_DURATION: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Language,
+    Prefix,
+    DjRole,
+    Volume,
+    VoteSkip,
+    Announcements,
+    MusicChannels,
+    QueueLimit,
+    MaxDuration,
+    RequestChannel,
+    PlayReaction,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 11] = [
+        Setting::Language,
+        Setting::Prefix,
+        Setting::DjRole,
+        Setting::Volume,
+        Setting::VoteSkip,
+        Setting::Announcements,
+        Setting::MusicChannels,
+        Setting::QueueLimit,
+        Setting::MaxDuration,
+        Setting::RequestChannel,
+        Setting::PlayReaction,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Setting::Language => "language",
+            Setting::Prefix => "prefix",
+            Setting::DjRole => "dj_role",
+            Setting::Volume => "volume",
+            Setting::VoteSkip => "vote_skip",
+            Setting::Announcements => "announcements",
+            Setting::MusicChannels => "music_channels",
+            Setting::QueueLimit => "queue_limit",
+            Setting::MaxDuration => "max_duration",
+            Setting::RequestChannel => "request_channel",
+            Setting::PlayReaction => "play_reaction",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|setting| setting.name() == name)
+    }
+
+    fn label_key(self) -> String {
+        format!("guild_settings.{}", self.name())
+    }
+}
+
+fn parse_channel_id(guild: &DiscordGuild, value: &str) -> Result<ChannelId, LocalizedError> {
+    let channel = parse_channel(value)
+        .or_else(|| value.parse().ok())
+        .and_then(|id| guild.channels.get(&ChannelId(id)));
+    match channel {
+        Some(channel) if channel.kind == ChannelType::Text => Ok(channel.id),
+        _ => Err(LocalizedError::with(
+            "error.not_channel",
+            json!({ "value": value }),
+        )),
+    }
+}
+
+/// Parse a Unicode or custom emoji.
+fn parse_emoji(value: &str) -> Result<ReactionType, LocalizedError> {
+    let err = || LocalizedError::with("error.not_emoji", json!({ "value": value }));
+    let emoji = value.parse::<ReactionType>().map_err(|_| err())?;
+
+    // Anything that is not a custom emoji is parsed as Unicode
+    let valid = match &emoji {
+        ReactionType::Unicode(text) => !text.is_ascii() && !text.contains(char::is_whitespace),
+        _ => true,
+    };
+    if valid {
+        Ok(emoji)
+    } else {
+        Err(err())
+    }
+}
+
+fn parse_percentage(
+    value: &str, range: std::ops::RangeInclusive<u8>,
+) -> Result<u8, LocalizedError> {
+    value
+        .trim_end_matches('%')
+        .parse::<u8>()
+        .ok()
+        .filter(|value| range.contains(value))
+        .ok_or_else(|| {
+            LocalizedError::with(
+                "error.percentage",
+                json!({ "min": range.start(), "max": range.end() }),
+            )
+        })
+}
+
+/// Validate `value` and store it as the new value of `setting`.
+async fn update(
+    ctx: &Context, guild: &DiscordGuild, setting: Setting, value: &str,
+) -> CommandResult {
+    let reset = RESET.contains(&value.to_lowercase().as_str());
+
+    match setting {
+        Setting::Language => {
+            let lang = if reset {
+                None
+            } else {
+                let lang = value.parse::<Language>().map_err(|_| {
+                    let names: Vec<String> = Language::all()
+                        .iter()
+                        .map(|l| format!("{} ({})", l.name(), l))
+                        .collect();
+                    LocalizedError::with(
+                        "error.unknown_language",
+                        json!({ "language": value, "languages": names.join(", ") }),
+                    )
+                })?;
+                Some(lang)
+            };
+            database::update_guild(guild.id, |g| g.lang = lang).await?;
+        },
+        Setting::Prefix => {
+            let list = if reset {
+                Vec::new()
+            } else {
+                value.split_whitespace().map(String::from).collect()
+            };
+            prefixes::set_prefixes(guild.id, list).await?;
+        },
+        Setting::DjRole => {
+            let role = if reset {
+                None
+            } else {
+                let id = parse_role(value)
+                    .or_else(|| value.parse().ok())
+                    .filter(|id| guild.roles.contains_key(&RoleId(*id)))
+                    .ok_or_else(|| {
+                        LocalizedError::with("error.not_role", json!({ "value": value }))
+                    })?;
+                Some(id)
+            };
+            database::update_guild(guild.id, |g| g.dj_role = role).await?;
+        },
+        Setting::Volume => {
+            let volume = if reset {
+                Guild::new(guild.id).default_volume
+            } else {
+                parse_percentage(value, 0..=200)?
+            };
+            database::update_guild(guild.id, |g| g.default_volume = volume).await?;
+        },
+        Setting::VoteSkip => {
+            let threshold = if reset {
+                Guild::new(guild.id).vote_skip_threshold
+            } else {
+                parse_percentage(value, 1..=100)?
+            };
+            database::update_guild(guild.id, |g| g.vote_skip_threshold = threshold).await?;
+        },
+        Setting::Announcements => {
+            let channel = if reset {
+                None
+            } else {
+                Some(parse_channel_id(guild, value)?.0)
+            };
+            database::update_guild(guild.id, |g| g.announcement_channel = channel).await?;
+        },
+        Setting::MusicChannels => {
+            let mut channels = Vec::new();
+            if !reset {
+                let mut seen = HashSet::new();
+                for value in value.split_whitespace() {
+                    let channel = parse_channel_id(guild, value)?.0;
+                    if seen.insert(channel) {
+                        channels.push(channel);
+                    }
+                }
+            }
+            database::update_guild(guild.id, |g| g.music_channels = channels).await?;
+        },
+        Setting::QueueLimit => {
+            let limit = if reset {
+                None
+            } else {
+                let limit = value
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|limit| (1..=MAX_QUEUE_LENGTH).contains(limit))
+                    .ok_or_else(|| {
+                        LocalizedError::with(
+                            "error.queue_limit",
+                            json!({ "max": MAX_QUEUE_LENGTH }),
+                        )
+                    })?;
+                Some(limit)
+            };
+            database::update_guild(guild.id, |g| g.max_queue_length = limit).await?;
+        },
+        Setting::MaxDuration => {
+            let duration = if reset {
+                None
+            } else {
+                let duration = parse_duration(value)?;
+                if duration < MIN_TRACK_DURATION {
+                    let min = MIN_TRACK_DURATION.as_secs();
+                    return Err(LocalizedError::with(
+                        "error.min_duration",
+                        json!({ "duration": min }),
+                    )
+                    .into());
+                }
+                Some(duration.as_secs())
+            };
+            database::update_guild(guild.id, |g| g.max_track_duration = duration).await?;
+        },
+        Setting::RequestChannel => {
+            let channel = if reset {
+                None
+            } else {
+                Some(parse_channel_id(guild, value)?)
+            };
+
+            // The dashboard moves with the channel
+            let previous = database::get_guild(guild.id)
+                .await
+                .and_then(|g| Some((g.request_channel?, g.request_message?)));
+            if let Some((channel, message)) = previous {
+                if let Err(err) = ChannelId(channel).delete_message(ctx, message).await {
+                    warn!("Failed to delete request channel dashboard: {:?}", err);
+                }
+            }
+            let message = match channel {
+                Some(channel) => Some(dashboard::create(&ctx.http, guild.id, channel).await?.0),
+                None => None,
+            };
+
+            database::update_guild(guild.id, |g| {
+                g.request_channel = channel.map(|channel| channel.0);
+                g.request_message = message;
+            })
+            .await?;
+            dashboard::forget_request_channel(guild.id).await;
+        },
+        Setting::PlayReaction => {
+            let emoji = if reset {
+                None
+            } else {
+                Some(parse_emoji(value)?.to_string())
+            };
+            database::update_guild(guild.id, |g| g.play_reaction = emoji).await?;
+            play::forget_play_reaction(guild.id).await;
+        },
+    }
+    Ok(())
+}
+
+/// Current value of `setting`, formatted for the settings embed.
+async fn display(lang: Language, settings: &Guild, setting: Setting) -> String {
+    let none = || lang.get("guild_settings.none");
+    let unlimited = || lang.get("guild_settings.unlimited");
+
+    match setting {
+        Setting::Language => match settings.lang {
+            Some(guild_lang) => guild_lang.name(),
+            None => lang
+                .translate(
+                    "guild_settings.automatic_language",
+                    json!({ "language": settings.language().name() }),
+                )
+                .unwrap_or_else(|_| settings.language().name()),
+        },
+        Setting::Prefix => get_prefixes(GuildId(settings.discord_id))
+            .await
+            .iter()
+            .map(|prefix| format!("`{}`", prefix))
+            .collect::<Vec<_>>()
+            .join(", "),
+        Setting::DjRole => settings
+            .dj_role
+            .map(|role| format!("<@&{}>", role))
+            .unwrap_or_else(none),
+        Setting::Volume => format!("{}%", settings.default_volume),
+        Setting::VoteSkip => format!("{}%", settings.vote_skip_threshold),
+        Setting::Announcements => settings
+            .announcement_channel
+            .map(|channel| format!("<#{}>", channel))
+            .unwrap_or_else(none),
+        Setting::MusicChannels if settings.music_channels.is_empty() => {
+            lang.get("guild_settings.any_channel")
+        },
+        Setting::MusicChannels => settings
+            .music_channels
+            .iter()
+            .map(|channel| format!("<#{}>", channel))
+            .collect::<Vec<_>>()
+            .join(" "),
+        Setting::QueueLimit => settings
+            .max_queue_length
+            .map(|limit| limit.to_string())
+            .unwrap_or_else(unlimited),
+        Setting::MaxDuration => settings
+            .max_track_duration
+            .map(|secs| lang.format_duration(Duration::from_secs(secs)))
+            .unwrap_or_else(unlimited),
+        Setting::RequestChannel => settings
+            .request_channel
+            .map(|channel| format!("<#{}>", channel))
+            .unwrap_or_else(none),
+        Setting::PlayReaction => settings.play_reaction.clone().unwrap_or_else(none),
+    }
+}
+
+async fn send_settings(
+    ctx: &Context, invocation: &Invocation<'_>, guild: &DiscordGuild, updated: Option<Setting>,
+) -> CommandResult {
+    let lang = invocation.language().await;
+    let settings = database::get_guild(guild.id)
+        .await
+        .unwrap_or_else(|| Guild::new(guild.id));
+
+    let mut fields = Vec::with_capacity(Setting::ALL.len());
+    for setting in Setting::ALL.iter().copied() {
+        let name = format!(
+            "{} (`{}`)",
+            lang.get(setting.label_key().as_str()),
+            setting.name()
+        );
+        fields.push((name, display(lang, &settings, setting).await, true));
+    }
+
+    let title = lang.translate("guild_settings.title", json!({ "guild": guild.name }))?;
+    let description = match updated {
+        Some(setting) => lang.translate(
+            "guild_settings.updated",
+            json!({ "setting": lang.get(setting.label_key().as_str()) }),
+        )?,
+        None => lang.get("guild_settings.usage"),
+    };
+
+    invocation
+        .send_embed(ctx, |e| {
+            invocation.embed_author(e);
+            e.color(Colour::DARK_BLUE);
+            e.title(title);
+            e.description(description);
+            e.fields(fields);
+            if let Some(icon) = guild.icon_url() {
+                e.thumbnail(icon);
+            }
+            e
+        })
+        .await?;
+    Ok(())
+}
 
 #[command]
 #[only_in(guilds)]
 #[bucket = "basic"]
 #[required_permissions("ADMINISTRATOR")]
-#[description = "Configure Guild Settings"]
-async fn guild_settings(_ctx: &Context, _msg: &Message) -> CommandResult {
-    Ok(())
+async fn guild_settings(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let setting = args.single::<String>().ok();
+    run(ctx, &msg.into(), setting.as_deref(), args.remains()).await
+}
+
+pub async fn run(
+    ctx: &Context, invocation: &Invocation<'_>, setting: Option<&str>, value: Option<&str>,
+) -> CommandResult {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
+
+    let setting = match setting {
+        Some(name) => Setting::from_name(name).ok_or_else(|| {
+            let names: Vec<&str> = Setting::ALL.iter().map(|s| s.name()).collect();
+            LocalizedError::with(
+                "error.unknown_setting",
+                json!({ "setting": name, "settings": names.join(", ") }),
+            )
+        })?,
+        None => return send_settings(ctx, invocation, &guild, None).await,
+    };
+    let value = value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| LocalizedError::new("error.setting_value"))?;
+
+    update(ctx, &guild, setting, value).await?;
+    send_settings(ctx, invocation, &guild, Some(setting)).await
 }
@@ -1,24 +1,31 @@
+use serde_json::json;
 use serenity::client::bridge::gateway::ShardId;
 use serenity::client::Context;
 use serenity::framework::standard::macros::command;
 use serenity::framework::standard::CommandResult;
 use serenity::model::channel::Message;
 
+use crate::invocation::Invocation;
 use crate::ShardManagerContainer;
 
 #[command]
 #[aliases("lag", "ping")]
 #[bucket = "basic"]
-#[description = "Check bot shard latency with the Discord Gateway."]
 async fn latency(ctx: &Context, msg: &Message) -> CommandResult {
+    run(ctx, &msg.into()).await
+}
+
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>) -> CommandResult {
     // The shard manager is an interface for mutating, stopping, restarting, and
     // retrieving information about shards.
     let data = ctx.data.read().await;
+    let lang = invocation.language().await;
 
     let shard_manager = if let Some(v) = data.get::<ShardManagerContainer>() {
         v
     } else {
-        msg.reply(ctx, "There was a problem getting the shard manager")
+        invocation
+            .reply(ctx, lang.get("latency.shard_manager"))
             .await?;
         return Ok(());
     };
@@ -32,18 +39,20 @@ async fn latency(ctx: &Context, msg: &Message) -> CommandResult {
     let runner = if let Some(runner) = runners.get(&ShardId(ctx.shard_id)) {
         runner
     } else {
-        msg.reply(ctx, "No shard found").await?;
+        invocation.reply(ctx, lang.get("latency.no_shard")).await?;
         return Ok(());
     };
 
     if let Some(latency) = runner.latency {
-        msg.reply(
-            ctx,
-            &format!("The current instance latency is {:?}", latency),
-        )
-        .await?;
+        let text = lang.translate(
+            "latency.current",
+            json!({ "latency": format!("{:?}", latency) }),
+        )?;
+        invocation.reply(ctx, text).await?;
     } else {
-        msg.reply(ctx, "Latency data is not available.").await?;
+        invocation
+            .reply(ctx, lang.get("latency.unavailable"))
+            .await?;
     }
 
     Ok(())
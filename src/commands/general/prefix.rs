@@ -0,0 +1,54 @@
+use serde_json::json;
+use serenity::client::Context;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::channel::Message;
+
+use crate::invocation::Invocation;
+use crate::lang::LocalizedError;
+use crate::prefixes::{get_prefixes, set_prefixes};
+use crate::utils::send_translated_info;
+
+#[command]
+#[only_in(guilds)]
+#[bucket = "basic"]
+#[required_permissions("ADMINISTRATOR")]
+async fn prefix(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    run(ctx, &msg.into(), args.remains()).await
+}
+
+pub async fn run(
+    ctx: &Context, invocation: &Invocation<'_>, prefixes: Option<&str>,
+) -> CommandResult {
+    let guild_id = invocation
+        .guild_id()
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
+
+    let key = match prefixes {
+        None => "prefix.current",
+        Some("reset") => {
+            set_prefixes(guild_id, Vec::new()).await?;
+            "prefix.reset"
+        },
+        Some(prefixes) => {
+            let prefixes = prefixes.split_whitespace().map(String::from).collect();
+            set_prefixes(guild_id, prefixes).await?;
+            "prefix.updated"
+        },
+    };
+
+    let prefixes = get_prefixes(guild_id)
+        .await
+        .iter()
+        .map(|prefix| format!("`{}`", prefix))
+        .collect::<Vec<_>>()
+        .join(", ");
+    send_translated_info(
+        "prefix.title",
+        key,
+        json!({ "prefixes": prefixes }),
+        invocation,
+        ctx,
+    )
+    .await
+}
@@ -0,0 +1,39 @@
+use serde_json::json;
+use serenity::client::Context;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::CommandResult;
+use serenity::model::channel::Message;
+
+use crate::invocation::Invocation;
+use crate::lang::{registry, LocalizedError};
+use crate::utils::send_translated_info;
+
+#[command]
+#[owners_only]
+#[help_available(false)]
+async fn reload_translations(ctx: &Context, msg: &Message) -> CommandResult {
+    run(ctx, &msg.into()).await
+}
+
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>) -> CommandResult {
+    let languages = registry::reload().map_err(|errors| {
+        let errors: Vec<String> = errors.iter().map(|err| format!("`{}`", err)).collect();
+        LocalizedError::with(
+            "error.reload_translations",
+            json!({ "errors": errors.join("\n") }),
+        )
+    })?;
+
+    let names: Vec<String> = languages
+        .iter()
+        .map(|l| format!("{} ({})", l.name(), l))
+        .collect();
+    send_translated_info(
+        "translations.title",
+        "translations.reloaded",
+        json!({ "count": languages.len(), "languages": names.join(", ") }),
+        invocation,
+        ctx,
+    )
+    .await
+}
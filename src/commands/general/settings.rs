@@ -1,11 +1,219 @@
+use serde_json::json;
 use serenity::client::Context;
 use serenity::framework::standard::macros::command;
-use serenity::framework::standard::CommandResult;
+use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::channel::Message;
+use serenity::utils::Colour;
+
+use crate::audio::source::SearchSource;
+use crate::database;
+use crate::database::user::{ReplyStyle, User};
+use crate::invocation::Invocation;
+use crate::lang::{Language, LocalizedError};
+
+/// Value that resets a setting to its default.
+const RESET: &[&str] = &["none", "reset", "default"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Language,
+    DmNotifications,
+    Replies,
+    Search,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 4] = [
+        Setting::Language,
+        Setting::DmNotifications,
+        Setting::Replies,
+        Setting::Search,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Setting::Language => "language",
+            Setting::DmNotifications => "dm_notifications",
+            Setting::Replies => "replies",
+            Setting::Search => "search",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|setting| setting.name() == name)
+    }
+
+    fn label_key(self) -> String {
+        format!("settings.{}", self.name())
+    }
+}
+
+fn parse_toggle(value: &str) -> Result<bool, LocalizedError> {
+    match value.to_lowercase().as_str() {
+        "on" | "yes" | "true" | "enable" | "enabled" => Ok(true),
+        "off" | "no" | "false" | "disable" | "disabled" => Ok(false),
+        _ => Err(LocalizedError::with(
+            "error.toggle",
+            json!({ "value": value }),
+        )),
+    }
+}
+
+fn parse_reply_style(value: &str) -> Result<ReplyStyle, LocalizedError> {
+    match value.to_lowercase().as_str() {
+        "embed" => Ok(ReplyStyle::Embed),
+        "compact" => Ok(ReplyStyle::Compact),
+        _ => Err(LocalizedError::with(
+            "error.reply_style",
+            json!({ "value": value }),
+        )),
+    }
+}
+
+/// Validate `value` and store it as the new value of `setting`.
+async fn update(invocation: &Invocation<'_>, setting: Setting, value: &str) -> CommandResult {
+    let reset = RESET.contains(&value.to_lowercase().as_str());
+    let author = invocation.author();
+
+    match setting {
+        Setting::Language => {
+            let lang = if reset {
+                None
+            } else {
+                let lang = value.parse::<Language>().map_err(|_| {
+                    let names: Vec<String> = Language::all()
+                        .iter()
+                        .map(|l| format!("{} ({})", l.name(), l))
+                        .collect();
+                    LocalizedError::with(
+                        "error.unknown_language",
+                        json!({ "language": value, "languages": names.join(", ") }),
+                    )
+                })?;
+                Some(lang)
+            };
+            database::update_user(author, |u| u.lang = lang).await?;
+        },
+        Setting::DmNotifications => {
+            let enabled = !reset && parse_toggle(value)?;
+            database::update_user(author, |u| u.dm_notifications = enabled).await?;
+        },
+        Setting::Replies => {
+            let style = if reset {
+                ReplyStyle::default()
+            } else {
+                parse_reply_style(value)?
+            };
+            database::update_user(author, |u| u.reply_style = style).await?;
+        },
+        Setting::Search => {
+            let source = if reset {
+                SearchSource::default()
+            } else {
+                value.parse::<SearchSource>().map_err(|_| {
+                    let names: Vec<&str> = SearchSource::ALL.iter().map(|s| s.name()).collect();
+                    LocalizedError::with(
+                        "error.unknown_source",
+                        json!({ "source": value, "sources": names.join(", ") }),
+                    )
+                })?
+            };
+            database::update_user(author, |u| u.search_source = source).await?;
+        },
+    }
+    Ok(())
+}
+
+/// Current value of `setting`, formatted for the settings embed.
+fn display(lang: Language, settings: &User, setting: Setting) -> String {
+    match setting {
+        Setting::Language => match settings.lang {
+            Some(user_lang) => user_lang.get("language.name"),
+            None => lang.get("settings.server_language"),
+        },
+        Setting::DmNotifications if settings.dm_notifications => lang.get("settings.enabled"),
+        Setting::DmNotifications => lang.get("settings.disabled"),
+        Setting::Replies => match settings.reply_style {
+            ReplyStyle::Embed => lang.get("settings.embed"),
+            ReplyStyle::Compact => lang.get("settings.compact"),
+        },
+        Setting::Search => lang.get(format!("settings.source.{}", settings.search_source.name())),
+    }
+}
+
+async fn send_settings(
+    ctx: &Context, invocation: &Invocation<'_>, updated: Option<Setting>,
+) -> CommandResult {
+    let author = invocation.author();
+    let lang = invocation.language().await;
+    let settings = database::get_user(author.id)
+        .await
+        .unwrap_or_else(|| User::new(author));
+
+    let fields: Vec<_> = Setting::ALL
+        .iter()
+        .copied()
+        .map(|setting| {
+            let name = format!(
+                "{} (`{}`)",
+                lang.get(setting.label_key().as_str()),
+                setting.name()
+            );
+            (name, display(lang, &settings, setting), true)
+        })
+        .collect();
+
+    let title = lang.translate("settings.title", json!({ "user": author.name }))?;
+    let description = match updated {
+        Some(setting) => lang.translate(
+            "settings.updated",
+            json!({ "setting": lang.get(setting.label_key().as_str()) }),
+        )?,
+        None => lang.get("settings.usage"),
+    };
+
+    invocation
+        .send_embed(ctx, |e| {
+            invocation.embed_author(e);
+            e.color(Colour::DARK_BLUE);
+            e.title(title);
+            e.description(description);
+            e.fields(fields);
+            e
+        })
+        .await?;
+    Ok(())
+}
 
 #[command]
 #[bucket = "basic"]
-#[description = "Configure Personal User Settings"]
-async fn settings(_ctx: &Context, _msg: &Message) -> CommandResult {
-    Ok(())
+async fn settings(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let setting = args.single::<String>().ok();
+    run(ctx, &msg.into(), setting.as_deref(), args.remains()).await
+}
+
+pub async fn run(
+    ctx: &Context, invocation: &Invocation<'_>, setting: Option<&str>, value: Option<&str>,
+) -> CommandResult {
+    let setting = match setting {
+        Some(name) => Setting::from_name(name).ok_or_else(|| {
+            let names: Vec<&str> = Setting::ALL.iter().map(|s| s.name()).collect();
+            LocalizedError::with(
+                "error.unknown_setting",
+                json!({ "setting": name, "settings": names.join(", ") }),
+            )
+        })?,
+        None => return send_settings(ctx, invocation, None).await,
+    };
+    let value = value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| LocalizedError::new("error.setting_value"))?;
+
+    update(invocation, setting, value).await?;
+    send_settings(ctx, invocation, Some(setting)).await
 }
@@ -0,0 +1,243 @@
+use std::time::Duration;
+
+use serde_json::json;
+use serenity::client::Context;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::channel::Message;
+use serenity::utils::Colour;
+
+use crate::constants::MUSIC_ICON;
+use crate::database::history::{self, Scope, Stats, TopSong, Window};
+use crate::invocation::Invocation;
+use crate::lang::{Language, LocalizedError, TranslationError};
+use crate::paginator::{split_pages, MAX_FIELD};
+
+/// Longest song title shown in the rankings, in characters.
+const MAX_TITLE: usize = 60;
+
+/// Title of a song in the rankings, linked to the song when possible.
+fn song_title(lang: Language, song: &TopSong) -> String {
+    let mut title: String = song.title.chars().take(MAX_TITLE).collect();
+    if title.is_empty() {
+        title = lang.get("song.unknown");
+    } else if song.title.chars().count() > MAX_TITLE {
+        title.push('\u{2026}');
+    }
+    match song.url.as_deref() {
+        Some(url) if url.starts_with("http") => format!("[{}]({})", title, url),
+        _ => title,
+    }
+}
+
+fn plays(lang: Language, plays: i64) -> Result<String, TranslationError> {
+    lang.translate("stats.plays", json!({ "count": plays }))
+}
+
+/// Lines of a ranking that fit in an embed field. The last ones are left out
+/// when links make them too long.
+fn ranking(lines: &[String]) -> String {
+    split_pages(lines.iter().map(String::as_str), MAX_FIELD)
+        .into_iter()
+        .next()
+        .unwrap_or_default()
+}
+
+fn top_songs(lang: Language, songs: &[TopSong]) -> Result<String, TranslationError> {
+    let mut lines = Vec::with_capacity(songs.len());
+    for (index, song) in songs.iter().enumerate() {
+        lines.push(format!(
+            "{}. {} \u{2022} {}",
+            index + 1,
+            song_title(lang, song),
+            plays(lang, song.plays)?
+        ));
+    }
+    Ok(ranking(&lines))
+}
+
+/// Fields with the totals and rankings of some statistics.
+fn stats_fields(
+    lang: Language, stats: &Stats,
+) -> Result<Vec<(String, String, bool)>, TranslationError> {
+    let mut fields = vec![
+        (
+            lang.get("stats.total"),
+            lang.translate(
+                "stats.total_value",
+                json!({ "time": stats.totals.listened, "count": stats.totals.plays }),
+            )?,
+            false,
+        ),
+        (
+            lang.get("stats.top_songs"),
+            top_songs(lang, &stats.top_songs)?,
+            false,
+        ),
+    ];
+
+    if !stats.top_requesters.is_empty() {
+        let mut lines = Vec::with_capacity(stats.top_requesters.len());
+        for (index, requester) in stats.top_requesters.iter().enumerate() {
+            let listened = Duration::from_secs(requester.listened.max(0) as u64);
+            lines.push(format!(
+                "{}. <@{}> \u{2022} {} \u{2022} {}",
+                index + 1,
+                requester.user(),
+                plays(lang, requester.plays)?,
+                lang.format_duration(listened)
+            ));
+        }
+        fields.push((lang.get("stats.top_requesters"), ranking(&lines), false));
+    }
+    Ok(fields)
+}
+
+async fn send_stats(
+    ctx: &Context, invocation: &Invocation<'_>, lang: Language, title: String, window: Window,
+    stats: &Stats,
+) -> CommandResult {
+    let fields = stats_fields(lang, stats)?;
+    let window = format!("stats.window.{}", window.name());
+    let description = lang.get(window.as_str());
+    invocation
+        .send_embed(ctx, |e| {
+            invocation.embed_author(e);
+            e.thumbnail(MUSIC_ICON);
+            e.color(Colour::DARK_PURPLE);
+            e.title(title);
+            e.description(description);
+            e.fields(fields);
+            e
+        })
+        .await?;
+    Ok(())
+}
+
+fn parse_window(window: Option<&str>) -> Result<Window, LocalizedError> {
+    match window {
+        Some(window) => {
+            Window::from_name(window).ok_or_else(|| LocalizedError::new("error.stats_window"))
+        },
+        None => Ok(Window::All),
+    }
+}
+
+#[command]
+#[aliases("statistics", "top")]
+#[bucket = "basic"]
+#[sub_commands(me, wrapped)]
+async fn stats(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let window = parse_window(args.current())?;
+    run(ctx, &msg.into(), window).await
+}
+
+/// Show the totals and rankings of the songs played in the guild.
+pub async fn run(ctx: &Context, invocation: &Invocation<'_>, window: Window) -> CommandResult {
+    let guild = invocation
+        .guild(ctx)
+        .await
+        .ok_or_else(|| LocalizedError::new("error.guild"))?;
+
+    let stats = history::stats(Scope::Guild(guild.id), window).await?;
+    if stats.totals.plays == 0 {
+        return Err(LocalizedError::new("stats.empty").into());
+    }
+
+    let lang = invocation.language().await;
+    let title = lang.translate("stats.title", json!({ "guild": guild.name }))?;
+    send_stats(ctx, invocation, lang, title, window, &stats).await
+}
+
+#[command]
+#[bucket = "basic"]
+async fn me(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let window = parse_window(args.current())?;
+    run_me(ctx, &msg.into(), window).await
+}
+
+/// Show the totals and rankings of the songs requested by the user, in every
+/// guild.
+pub async fn run_me(ctx: &Context, invocation: &Invocation<'_>, window: Window) -> CommandResult {
+    let author = invocation.author();
+    let stats = history::stats(Scope::User(author.id), window).await?;
+    if stats.totals.plays == 0 {
+        return Err(LocalizedError::new("stats.me_empty").into());
+    }
+
+    let lang = invocation.language().await;
+    let title = lang.translate("stats.me_title", json!({ "user": author.name }))?;
+    send_stats(ctx, invocation, lang, title, window, &stats).await
+}
+
+#[command]
+#[bucket = "basic"]
+async fn wrapped(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let year = match args.current() {
+        Some(_) => Some(
+            args.single::<i32>()
+                .map_err(|_| LocalizedError::new("error.stats_year"))?,
+        ),
+        None => None,
+    };
+    run_wrapped(ctx, &msg.into(), year).await
+}
+
+/// Show a summary of the songs requested by the user during a year, the
+/// current one by default.
+pub async fn run_wrapped(
+    ctx: &Context, invocation: &Invocation<'_>, year: Option<i32>,
+) -> CommandResult {
+    let year = year.unwrap_or_else(history::current_year);
+    if !(1970..=history::current_year()).contains(&year) {
+        return Err(LocalizedError::new("error.stats_year").into());
+    }
+    let author = invocation.author();
+    let wrapped = history::wrapped(author.id, year).await?;
+    if wrapped.totals.plays == 0 {
+        return Err(LocalizedError::with(
+            "stats.wrapped_empty",
+            json!({ "year": year.to_string() }),
+        )
+        .into());
+    }
+
+    let lang = invocation.language().await;
+    let mut summary = vec![
+        lang.translate(
+            "stats.wrapped.time",
+            json!({ "time": wrapped.totals.listened }),
+        )?,
+        lang.translate(
+            "stats.wrapped.songs",
+            json!({ "count": wrapped.totals.plays, "songs": wrapped.songs }),
+        )?,
+        lang.translate(
+            "stats.wrapped.days",
+            json!({ "days": wrapped.days, "guilds": wrapped.guilds }),
+        )?,
+    ];
+    if let Some(month) = wrapped.top_month {
+        let month = format!("stats.month.{}", month);
+        let month = lang.get(month.as_str());
+        summary.push(lang.translate("stats.wrapped.month", json!({ "month": month }))?);
+    }
+    let title = lang.translate(
+        "stats.wrapped.title",
+        json!({ "user": author.name, "year": year.to_string() }),
+    )?;
+    let top_songs = top_songs(lang, &wrapped.top_songs)?;
+
+    invocation
+        .send_embed(ctx, |e| {
+            invocation.embed_author(e);
+            e.thumbnail(MUSIC_ICON);
+            e.color(Colour::DARK_PURPLE);
+            e.title(title);
+            e.description(summary.join("\n"));
+            e.field(lang.get("stats.top_songs"), top_songs, false);
+            e
+        })
+        .await?;
+    Ok(())
+}
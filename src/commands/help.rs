@@ -1,30 +1,220 @@
 use std::collections::HashSet;
 
+use serde_json::json;
 use serenity::client::Context;
 use serenity::framework::standard::macros::help;
 use serenity::framework::standard::{
-    help_commands, Args, CommandGroup, CommandResult, HelpOptions,
+    Args, Command, CommandGroup, CommandOptions, CommandResult, GroupOptions, HelpOptions, OnlyIn,
 };
 use serenity::model::channel::Message;
 use serenity::model::id::UserId;
+use serenity::model::Permissions;
+use serenity::utils::Colour;
+
+use crate::invocation::Invocation;
+use crate::lang::{Language, LocalizedError};
+use crate::prefixes::{default_prefix, get_prefixes};
+
+/// Translation key of a part of the help of a command, like `summary`.
+pub fn help_key(command: &str, part: &str) -> String {
+    format!("help.command.{}.{}", command, part)
+}
+
+/// What the author of the invocation is allowed to see.
+struct Viewer {
+    owner: bool,
+    in_guild: bool,
+    /// Permissions in the guild, unknown in direct messages.
+    permissions: Option<Permissions>,
+}
+
+impl Viewer {
+    async fn new(ctx: &Context, invocation: &Invocation<'_>, owners: &HashSet<UserId>) -> Self {
+        let author = invocation.author().id;
+        let permissions = match invocation.guild(ctx).await {
+            Some(guild) => guild.member_permissions(ctx, author).await.ok(),
+            None => None,
+        };
+        Self {
+            owner: owners.contains(&author),
+            in_guild: invocation.guild_id().is_some(),
+            permissions,
+        }
+    }
+
+    fn allows(
+        &self, help_available: bool, owners_only: bool, only_in: OnlyIn, required: Permissions,
+    ) -> bool {
+        let place = match only_in {
+            OnlyIn::Guild => self.in_guild,
+            OnlyIn::Dm => !self.in_guild,
+            _ => true,
+        };
+        let permissions = required.is_empty()
+            || self
+                .permissions
+                .map_or(false, |permissions| permissions.contains(required));
+        help_available && (self.owner || !owners_only) && place && permissions
+    }
+
+    fn can_see_group(&self, options: &GroupOptions) -> bool {
+        self.allows(
+            options.help_available,
+            options.owners_only,
+            options.only_in,
+            options.required_permissions,
+        )
+    }
+
+    fn can_see(&self, options: &CommandOptions) -> bool {
+        self.allows(
+            options.help_available,
+            options.owners_only,
+            options.only_in,
+            options.required_permissions,
+        )
+    }
+}
 
 #[help]
-#[individual_command_tip = "Hello! こんにちは！¡Hola! Bonjour! 您好! 안녕하세요~\n\nWelcome to \
-                            Dreamer, a powerful and easy to use Discord bot.\nIf you want more \
-                            information about a specific command, just pass the command as \
-                            argument."]
-#[command_not_found_text = "Could not find command: `{}`."]
-#[max_levenshtein_distance(3)]
-#[indention_prefix = "."]
-#[lacking_permissions = "Strike"]
-#[lacking_role = "Strike"]
-#[wrong_channel = "Strike"]
 async fn help(
-    context: &Context, msg: &Message, args: Args, help_options: &'static HelpOptions,
+    context: &Context, msg: &Message, args: Args, _help_options: &'static HelpOptions,
     groups: &[&'static CommandGroup], owners: HashSet<UserId>,
 ) -> CommandResult {
-    help_commands::with_embeds(context, msg, args, help_options, groups, owners)
-        .await
-        .ok_or("Failed to send help message")?;
+    run(context, &msg.into(), groups, &owners, args.current()).await
+}
+
+pub async fn run(
+    ctx: &Context, invocation: &Invocation<'_>, groups: &[&'static CommandGroup],
+    owners: &HashSet<UserId>, name: Option<&str>,
+) -> CommandResult {
+    let viewer = Viewer::new(ctx, invocation, owners).await;
+    let lang = invocation.language().await;
+    let prefix = match invocation.guild_id() {
+        Some(guild_id) => get_prefixes(guild_id).await.first().cloned(),
+        None => None,
+    }
+    .unwrap_or_else(|| default_prefix().to_string());
+
+    let visible: Vec<(&CommandGroup, Vec<&Command>)> = groups
+        .iter()
+        .copied()
+        .filter(|group| viewer.can_see_group(group.options))
+        .map(|group| {
+            let commands = group
+                .options
+                .commands
+                .iter()
+                .copied()
+                .filter(|command| viewer.can_see(command.options))
+                .collect();
+            (group, commands)
+        })
+        .collect();
+
+    match name {
+        Some(name) => {
+            let name = name.to_lowercase();
+            let command = visible
+                .iter()
+                .flat_map(|(_, commands)| commands.iter().copied())
+                .find(|command| command.options.names.contains(&name.as_str()))
+                .ok_or_else(|| {
+                    LocalizedError::with("error.unknown_command", json!({ "command": name }))
+                })?;
+            send_command(ctx, invocation, lang, &prefix, command).await
+        },
+        None => send_overview(ctx, invocation, lang, &prefix, &visible).await,
+    }
+}
+
+/// Every visible command with its summary, grouped by category.
+async fn send_overview(
+    ctx: &Context, invocation: &Invocation<'_>, lang: Language, prefix: &str,
+    groups: &[(&CommandGroup, Vec<&Command>)],
+) -> CommandResult {
+    let sections: Vec<String> = groups
+        .iter()
+        .filter(|(_, commands)| !commands.is_empty())
+        .map(|(group, commands)| {
+            let category = format!("help.group.{}", group.name.to_lowercase());
+            let lines: Vec<String> = commands
+                .iter()
+                .map(|command| {
+                    let name = command.options.names[0];
+                    let summary = lang.get(help_key(name, "summary").as_str());
+                    format!("`{}` {}", name, summary)
+                })
+                .collect();
+            format!("**{}**\n{}", lang.get(category.as_str()), lines.join("\n"))
+        })
+        .collect();
+
+    let intro = lang.translate("help.description", json!({ "prefix": prefix }))?;
+    invocation
+        .send_embed(ctx, |e| {
+            e.color(Colour::DARK_BLUE);
+            e.title(lang.get("help.title"));
+            e.description(format!("{}\n\n{}", intro, sections.join("\n\n")));
+            e
+        })
+        .await?;
+    Ok(())
+}
+
+/// Summary, usage, examples, aliases and subcommands of a command.
+async fn send_command(
+    ctx: &Context, invocation: &Invocation<'_>, lang: Language, prefix: &str, command: &Command,
+) -> CommandResult {
+    let names = command.options.names;
+    let name = names[0];
+    let summary = lang.get(help_key(name, "summary").as_str());
+
+    let mut fields = Vec::new();
+    let usage = match lang.find(&help_key(name, "usage")) {
+        Some(usage) => format!("`{}{} {}`", prefix, name, usage),
+        None => format!("`{}{}`", prefix, name),
+    };
+    fields.push((lang.get("help.usage"), usage, false));
+    if let Some(examples) = lang.find(&help_key(name, "examples")) {
+        let examples: Vec<String> = examples
+            .lines()
+            .map(|example| format!("`{}{}`", prefix, example))
+            .collect();
+        fields.push((lang.get("help.examples"), examples.join("\n"), false));
+    }
+    if names.len() > 1 {
+        let aliases: Vec<String> = names[1..].iter().map(|a| format!("`{}`", a)).collect();
+        fields.push((lang.get("help.aliases"), aliases.join(", "), false));
+    }
+    if !command.options.sub_commands.is_empty() {
+        let sub_commands: Vec<String> = command
+            .options
+            .sub_commands
+            .iter()
+            .map(|sub| {
+                let sub_name = sub.options.names[0];
+                let key = help_key(&format!("{}.{}", name, sub_name), "summary");
+                format!(
+                    "`{}{} {}` {}",
+                    prefix,
+                    name,
+                    sub_name,
+                    lang.get(key.as_str())
+                )
+            })
+            .collect();
+        fields.push((lang.get("help.subcommands"), sub_commands.join("\n"), false));
+    }
+
+    invocation
+        .send_embed(ctx, |e| {
+            e.color(Colour::DARK_BLUE);
+            e.title(format!("{}{}", prefix, name));
+            e.description(summary);
+            e.fields(fields);
+            e
+        })
+        .await?;
     Ok(())
 }
@@ -0,0 +1,407 @@
+use std::convert::TryFrom;
+
+use serde_json::Value;
+use serenity::builder::{CreateApplicationCommand, CreateApplicationCommands};
+use serenity::client::Context;
+use serenity::framework::standard::{Command, CommandGroup, CommandOptions, CommandResult, OnlyIn};
+use serenity::model::id::GuildId;
+use serenity::model::interactions::application_command::{
+    ApplicationCommand, ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    ApplicationCommandOptionType,
+};
+use serenity::model::interactions::InteractionResponseType;
+use serenity::model::Permissions;
+use tracing::{info, warn};
+
+use super::general::{guild_settings, settings};
+use super::help::help_key;
+use super::{audio, general, AUDIO_GROUP, GENERAL_GROUP};
+use crate::checks::{can_control_player, can_listen, can_use_channel, check_rate_limit};
+use crate::database::activity;
+use crate::database::history::Window;
+use crate::hooks::{report_error, send_error};
+use crate::invocation::Invocation;
+use crate::lang::{Language, LocalizedError};
+use crate::utils::parse_duration;
+
+/// Discord limit for the length of slash command descriptions.
+const MAX_DESCRIPTION: usize = 100;
+
+/// Prefix commands that are also registered as slash commands, with their
+/// group. Commands for the bot owners are only available with a prefix.
+fn commands() -> impl Iterator<Item = (&'static CommandGroup, &'static Command)> {
+    [&AUDIO_GROUP, &GENERAL_GROUP]
+        .iter()
+        .copied()
+        .flat_map(|group| {
+            group
+                .options
+                .commands
+                .iter()
+                .filter(|command| !command.options.owners_only)
+                .map(move |command| (group, *command))
+        })
+}
+
+fn find_command(name: &str) -> Option<(&'static CommandGroup, &'static Command)> {
+    commands().find(|(_, command)| command.options.names.first() == Some(&name))
+}
+
+/// Register every command as a slash command, in `guild_id` only when set.
+/// Guild commands are available immediately, which is useful for testing,
+/// while global commands can take up to an hour to show up.
+pub async fn register(ctx: &Context, guild_id: Option<GuildId>) -> Result<(), serenity::Error> {
+    let create = |c: &mut CreateApplicationCommands| {
+        for (_, command) in commands() {
+            c.create_application_command(|c| create_command(c, command.options));
+        }
+        c
+    };
+
+    let registered = match guild_id {
+        Some(guild_id) => guild_id.set_application_commands(&ctx.http, create).await?,
+        None => ApplicationCommand::set_global_application_commands(&ctx.http, create).await?,
+    };
+    info!("Registered {} slash commands", registered.len());
+    Ok(())
+}
+
+fn create_command<'a>(
+    c: &'a mut CreateApplicationCommand, options: &CommandOptions,
+) -> &'a mut CreateApplicationCommand {
+    let name = options.names.first().copied().unwrap_or_default();
+    c.name(name);
+    c.description(description(name));
+
+    match name {
+        "play" => {
+            c.create_option(|o| {
+                o.name("query")
+                    .description("URL or search query of the song to play")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            });
+        },
+        "seek" => {
+            c.create_option(|o| {
+                o.name("position")
+                    .description("Position to seek, like 1min 30s")
+                    .kind(ApplicationCommandOptionType::String)
+                    .required(true)
+            });
+        },
+        "info" => {
+            c.create_option(|o| {
+                o.name("position")
+                    .description("Position in the queue, the current song by default")
+                    .kind(ApplicationCommandOptionType::Integer)
+            });
+        },
+        "history" => {
+            c.create_option(|o| {
+                o.name("replay")
+                    .description("Number of a song of the history to play again")
+                    .kind(ApplicationCommandOptionType::Integer)
+            });
+        },
+        "volume" => {
+            c.create_option(|o| {
+                o.name("volume")
+                    .description("Volume from 0 to 200")
+                    .kind(ApplicationCommandOptionType::Integer)
+                    .required(true)
+            });
+        },
+        "fast_forward" | "rewind" => {
+            c.create_option(|o| {
+                o.name("seconds")
+                    .description("Seconds to move, 5 by default")
+                    .kind(ApplicationCommandOptionType::Integer)
+            });
+        },
+        "guild_settings" => {
+            c.create_option(|o| {
+                o.name("setting")
+                    .description("Setting to change, all of them are shown by default")
+                    .kind(ApplicationCommandOptionType::String);
+                for setting in guild_settings::Setting::ALL.iter() {
+                    o.add_string_choice(setting.name(), setting.name());
+                }
+                o
+            });
+            c.create_option(|o| {
+                o.name("value")
+                    .description("New value of the setting, or none to reset it")
+                    .kind(ApplicationCommandOptionType::String)
+            });
+        },
+        "settings" => {
+            c.create_option(|o| {
+                o.name("setting")
+                    .description("Setting to change, all of them are shown by default")
+                    .kind(ApplicationCommandOptionType::String);
+                for setting in settings::Setting::ALL.iter() {
+                    o.add_string_choice(setting.name(), setting.name());
+                }
+                o
+            });
+            c.create_option(|o| {
+                o.name("value")
+                    .description("New value of the setting, or none to reset it")
+                    .kind(ApplicationCommandOptionType::String)
+            });
+        },
+        "prefix" => {
+            c.create_option(|o| {
+                o.name("prefixes")
+                    .description("New prefixes separated by spaces, or reset")
+                    .kind(ApplicationCommandOptionType::String)
+            });
+        },
+        "stats" => {
+            c.create_option(|o| {
+                o.name("view")
+                    .description("Statistics to show, the ones of this server by default")
+                    .kind(ApplicationCommandOptionType::String)
+                    .add_string_choice("server", "server")
+                    .add_string_choice("me", "me")
+                    .add_string_choice("wrapped", "wrapped")
+            });
+            c.create_option(|o| {
+                o.name("window")
+                    .description("Time to count, all time by default")
+                    .kind(ApplicationCommandOptionType::String);
+                for window in Window::ALL.iter() {
+                    o.add_string_choice(window.name(), window.name());
+                }
+                o
+            });
+            c.create_option(|o| {
+                o.name("year")
+                    .description("Year of the wrapped summary, the current one by default")
+                    .kind(ApplicationCommandOptionType::Integer)
+            });
+        },
+        "lyrics" => {
+            c.create_option(|o| {
+                o.name("query")
+                    .description("Song to search, the current song by default")
+                    .kind(ApplicationCommandOptionType::String)
+            });
+            c.create_option(|o| {
+                o.name("live")
+                    .description("Follow the playback of the current song")
+                    .kind(ApplicationCommandOptionType::Boolean)
+            });
+        },
+        _ => {},
+    }
+    c
+}
+
+/// Summary of a command in the default language, shown as the description
+/// of its slash command.
+fn description(name: &str) -> String {
+    let summary = Language::default().get(help_key(name, "summary").as_str());
+    summary.chars().take(MAX_DESCRIPTION).collect()
+}
+
+fn option<'a>(
+    options: &'a [ApplicationCommandInteractionDataOption], name: &str,
+) -> Option<&'a Value> {
+    options
+        .iter()
+        .find(|option| option.name == name)?
+        .value
+        .as_ref()
+}
+
+fn string_option<'a>(
+    options: &'a [ApplicationCommandInteractionDataOption], name: &str,
+) -> Option<&'a str> {
+    option(options, name)?.as_str()
+}
+
+fn integer_option(options: &[ApplicationCommandInteractionDataOption], name: &str) -> Option<i64> {
+    option(options, name)?.as_i64()
+}
+
+fn boolean_option(options: &[ApplicationCommandInteractionDataOption], name: &str) -> Option<bool> {
+    option(options, name)?.as_bool()
+}
+
+/// Restrictions of the prefix command that the framework would otherwise
+/// enforce before running it.
+async fn check(
+    ctx: &Context, interaction: &ApplicationCommandInteraction, group: &CommandGroup,
+    options: &CommandOptions,
+) -> Result<(), LocalizedError> {
+    if options.only_in == OnlyIn::Guild && interaction.guild_id.is_none() {
+        return Err(LocalizedError::new("error.guild_only"));
+    }
+
+    if !options.required_permissions.is_empty() {
+        let permissions = interaction
+            .member
+            .as_ref()
+            .and_then(|member| member.permissions)
+            .unwrap_or_else(Permissions::empty);
+        if !permissions.contains(options.required_permissions) {
+            return Err(LocalizedError::new("error.permissions"));
+        }
+    }
+
+    for check in group.options.checks.iter().chain(options.checks) {
+        match (check.name, interaction.guild_id) {
+            ("Player", Some(guild_id)) => {
+                can_control_player(ctx, guild_id, interaction.user.id).await?
+            },
+            ("Listener", Some(guild_id)) => can_listen(ctx, guild_id, interaction.user.id).await?,
+            ("MusicChannel", Some(guild_id)) => {
+                can_use_channel(guild_id, interaction.channel_id).await?
+            },
+            _ => {},
+        }
+    }
+    // Counted last, so commands that can't run don't count
+    check_rate_limit(options, interaction.user.id).await
+}
+
+async fn run(
+    ctx: &Context, invocation: &Invocation<'_>, name: &str,
+    options: &[ApplicationCommandInteractionDataOption],
+) -> CommandResult {
+    match name {
+        "join" => audio::join::run(ctx, invocation).await,
+        "leave" => audio::leave::run(ctx, invocation).await,
+        "play" => {
+            let query = string_option(options, "query")
+                .ok_or_else(|| LocalizedError::new("error.play_query"))?;
+            audio::play::run(ctx, invocation, query).await
+        },
+        "next" => audio::next::run(ctx, invocation).await,
+        "queue" => audio::queue::run(ctx, invocation).await,
+        "stop" => audio::stop::run(ctx, invocation).await,
+        "pause" => audio::pause::run(ctx, invocation).await,
+        "resume" => audio::resume::run(ctx, invocation).await,
+        "seek" => {
+            let position = string_option(options, "position")
+                .ok_or_else(|| LocalizedError::new("error.seek_position"))?;
+            audio::seek::run(ctx, invocation, parse_duration(position)?).await
+        },
+        "lyrics" => {
+            if boolean_option(options, "live").unwrap_or(false) {
+                audio::lyrics::run_live(ctx, invocation).await
+            } else {
+                audio::lyrics::run(ctx, invocation, string_option(options, "query")).await
+            }
+        },
+        "volume" => {
+            let volume = integer_option(options, "volume")
+                .and_then(|volume| u8::try_from(volume).ok())
+                .ok_or_else(|| LocalizedError::new("error.volume"))?;
+            audio::volume::run(ctx, invocation, volume).await
+        },
+        "song" => audio::song::run(ctx, invocation).await,
+        "info" => {
+            let position = match integer_option(options, "position") {
+                Some(position) => Some(
+                    usize::try_from(position)
+                        .map_err(|_| LocalizedError::new("error.position_number"))?,
+                ),
+                None => None,
+            };
+            audio::info::run(ctx, invocation, position).await
+        },
+        "history" => match integer_option(options, "replay") {
+            Some(number) => {
+                let number = usize::try_from(number)
+                    .map_err(|_| LocalizedError::new("error.history_number"))?;
+                audio::history::run_replay(ctx, invocation, number).await
+            },
+            None => audio::history::run(ctx, invocation).await,
+        },
+        "shuffle" => audio::shuffle::run(ctx, invocation).await,
+        "back" => audio::back::run(ctx, invocation).await,
+        "fast_forward" | "rewind" => {
+            let seconds = integer_option(options, "seconds")
+                .map(|seconds| seconds.max(0) as u64)
+                .unwrap_or(5);
+            if name == "rewind" {
+                audio::rewind::run(ctx, invocation, seconds).await
+            } else {
+                audio::fast_forward::run(ctx, invocation, seconds).await
+            }
+        },
+        "repeat" => audio::repeat::run(ctx, invocation).await,
+        "stats" => {
+            let window = match string_option(options, "window") {
+                Some(window) => Window::from_name(window)
+                    .ok_or_else(|| LocalizedError::new("error.stats_window"))?,
+                None => Window::All,
+            };
+            match string_option(options, "view") {
+                Some("me") => general::stats::run_me(ctx, invocation, window).await,
+                Some("wrapped") => {
+                    let year = match integer_option(options, "year") {
+                        Some(year) => Some(
+                            i32::try_from(year)
+                                .map_err(|_| LocalizedError::new("error.stats_year"))?,
+                        ),
+                        None => None,
+                    };
+                    general::stats::run_wrapped(ctx, invocation, year).await
+                },
+                _ => general::stats::run(ctx, invocation, window).await,
+            }
+        },
+        "settings" => {
+            let setting = string_option(options, "setting");
+            let value = string_option(options, "value");
+            general::settings::run(ctx, invocation, setting, value).await
+        },
+        "guild_settings" => {
+            let setting = string_option(options, "setting");
+            let value = string_option(options, "value");
+            general::guild_settings::run(ctx, invocation, setting, value).await
+        },
+        "prefix" => general::prefix::run(ctx, invocation, string_option(options, "prefixes")).await,
+        "latency" => general::latency::run(ctx, invocation).await,
+        _ => Err(format!("Unknown command '{}'", name).into()),
+    }
+}
+
+/// Run the command behind a slash command interaction.
+pub async fn handle(ctx: &Context, interaction: &ApplicationCommandInteraction) {
+    let name = interaction.data.name.as_str();
+    let (group, command) = match find_command(name) {
+        Some(found) => found,
+        None => {
+            warn!("Received unknown slash command '{}'", name);
+            return;
+        },
+    };
+
+    // Answers are sent as follow-ups, as commands may take longer than the
+    // three seconds Discord waits for a response
+    let deferred = interaction
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+        })
+        .await;
+    if let Err(err) = deferred {
+        warn!("Failed to defer slash command '{}': {:?}", name, err);
+        return;
+    }
+
+    activity::record(&interaction.user).await;
+    let invocation = Invocation::from(interaction);
+    if let Err(reason) = check(ctx, interaction, group, command.options).await {
+        let lang = invocation.language().await;
+        send_error(ctx, &invocation, reason.translate(lang)).await;
+        return;
+    }
+    if let Err(why) = run(ctx, &invocation, name, &interaction.data.options).await {
+        report_error(ctx, &invocation, name, why).await;
+    }
+}
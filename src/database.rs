@@ -1,15 +1,24 @@
 use std::error::Error;
 use std::lazy::SyncOnceCell;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+use lyrics::SearchResult;
+use serenity::model::guild::Guild as DiscordGuild;
 use serenity::model::id::{GuildId, UserId};
+use serenity::model::user::User as DiscordUser;
 use wither::mongodb::{Client, Database};
 use wither::{bson, Model};
 
 use self::guild::Guild;
+use self::history::PlayedSong;
+use self::lyrics_cache::LyricsCache;
 use self::user::User;
 use crate::lang::Language;
 
+pub mod activity;
 pub mod guild;
+pub mod history;
+pub mod lyrics_cache;
 pub mod rpg;
 pub mod user;
 
@@ -27,12 +36,23 @@ pub async fn connect(uri: &str) -> Result<(), Box<dyn Error>> {
     DATABASE
         .set(db)
         .map_err(|_| "Database already initialized")?;
+    activity::spawn_flusher();
     Ok(())
 }
 
+/// Current UNIX time in seconds, as stored in documents.
+pub fn timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs() as i64)
+        .unwrap_or_default()
+}
+
 async fn sync_collections(db: &Database) -> Result<(), Box<dyn Error>> {
     User::sync(db).await?;
     Guild::sync(db).await?;
+    LyricsCache::sync(db).await?;
+    PlayedSong::sync(db).await?;
     Ok(())
 }
 
@@ -56,14 +76,116 @@ pub async fn get_guild(guild_id: GuildId) -> Option<Guild> {
         .flatten()
 }
 
-pub async fn get_language(user_id: UserId, guild_id: Option<GuildId>) -> Language {
-    if let Some(user) = get_user(user_id).await {
-        return user.lang;
+/// Language to answer a user in, from the first of: their language setting,
+/// the language setting of the guild, the `locale` of their Discord client,
+/// the preferred locale of the guild and the default language.
+pub async fn get_language(
+    user_id: UserId, guild_id: Option<GuildId>, locale: Option<&str>,
+) -> Language {
+    if let Some(lang) = get_user(user_id).await.and_then(|user| user.lang) {
+        return lang;
+    }
+    let guild = match guild_id {
+        Some(guild_id) => get_guild(guild_id).await,
+        None => None,
+    };
+    if let Some(lang) = guild.as_ref().and_then(|guild| guild.lang) {
+        return lang;
+    }
+    if let Some(lang) = locale.and_then(|locale| locale.parse().ok()) {
+        return lang;
+    }
+    guild.map(|guild| guild.language()).unwrap_or_default()
+}
+
+/// Change the settings of a user, creating its document on first use.
+pub async fn update_user<F>(user: &DiscordUser, update: F) -> wither::Result<User>
+where
+    F: FnOnce(&mut User), {
+    let mut document = get_user(user.id).await.unwrap_or_else(|| User::new(user));
+    update(&mut document);
+    document.save(&database(), None).await?;
+    Ok(document)
+}
+
+/// Change the settings of a guild, creating its document when missing.
+pub async fn update_guild<F>(guild_id: GuildId, update: F) -> wither::Result<Guild>
+where
+    F: FnOnce(&mut Guild), {
+    let mut guild = get_guild(guild_id)
+        .await
+        .unwrap_or_else(|| Guild::new(guild_id));
+    update(&mut guild);
+    guild.save(&database(), None).await?;
+    Ok(guild)
+}
+
+/// Record that the bot joined a guild, creating its document the first time
+/// and clearing the left date when it comes back. The preferred locale of the
+/// guild is updated as well, as it is received again on every connection.
+pub async fn join_guild(discord_guild: &DiscordGuild) -> wither::Result<()> {
+    let locale = Some(discord_guild.preferred_locale.clone());
+    let mut guild = match get_guild(discord_guild.id).await {
+        Some(guild) if guild.left_date.is_none() && guild.preferred_locale == locale => {
+            return Ok(())
+        },
+        Some(mut guild) if guild.left_date.is_some() => {
+            guild.join_date = timestamp();
+            guild.left_date = None;
+            guild
+        },
+        Some(guild) => guild,
+        None => Guild::new(discord_guild.id),
+    };
+    guild.preferred_locale = locale;
+    guild.save(&database(), None).await?;
+    Ok(())
+}
+
+/// Mark a guild as left. Its settings are kept in case the bot is added
+/// again.
+pub async fn leave_guild(guild_id: GuildId) -> wither::Result<()> {
+    if let Some(mut guild) = get_guild(guild_id).await {
+        guild.left_date = Some(timestamp());
+        guild.save(&database(), None).await?;
     }
-    if let Some(guild_id) = guild_id {
-        if let Some(guild) = get_guild(guild_id).await {
-            return guild.lang;
-        }
+    Ok(())
+}
+
+pub async fn get_guild_language(guild_id: GuildId) -> Language {
+    match get_guild(guild_id).await {
+        Some(guild) => guild.language(),
+        None => Language::default(),
     }
-    Language::default()
+}
+
+pub async fn get_lyrics(artist: &str, title: &str) -> Option<SearchResult> {
+    let filter = Some(bson::doc! {
+      "artist": artist,
+      "title": title
+    });
+    LyricsCache::find_one(&database(), filter, None)
+        .await
+        .ok()
+        .flatten()
+        .map(|cached| cached.result)
+}
+
+pub async fn save_lyrics(
+    artist: String, title: String, result: SearchResult,
+) -> Result<(), Box<dyn Error>> {
+    let filter = bson::doc! {
+      "artist": &artist,
+      "title": &title
+    };
+    let cached_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let mut cached = LyricsCache {
+        id: None,
+        artist,
+        title,
+        result,
+        cached_at,
+    };
+    cached.save(&database(), Some(filter)).await?;
+    Ok(())
 }
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::lazy::SyncLazy;
+use std::time::Duration;
+
+use serenity::model::id::UserId;
+use serenity::model::user::User as DiscordUser;
+use serenity::prelude::Mutex;
+use tracing::{debug, warn};
+use wither::bson::doc;
+use wither::mongodb::options::UpdateOptions;
+use wither::Model;
+
+use super::user::User;
+use super::{database, timestamp};
+
+/// How often the recorded activity is written to the database.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Latest activity of a user that has not been written yet.
+struct Seen {
+    username: String,
+    discriminator: u16,
+    last_seen: i64,
+}
+
+/// Users seen since the last flush. Recording activity only touches this
+/// map, so using the bot does not cause a write per message.
+static PENDING: SyncLazy<Mutex<HashMap<UserId, Seen>>> =
+    SyncLazy::new(|| Mutex::new(HashMap::new()));
+
+/// Record that a user used the bot. Their document is created or updated on
+/// the next flush.
+pub async fn record(user: &DiscordUser) {
+    if user.bot {
+        return;
+    }
+
+    let seen = Seen {
+        username: user.name.clone(),
+        discriminator: user.discriminator,
+        last_seen: timestamp(),
+    };
+    PENDING.lock().await.insert(user.id, seen);
+}
+
+/// Write the recorded activity periodically, in the background.
+pub fn spawn_flusher() {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            flush().await;
+        }
+    });
+}
+
+/// Upsert the document of every user seen since the last flush.
+async fn flush() {
+    let pending = std::mem::take(&mut *PENDING.lock().await);
+    if pending.is_empty() {
+        return;
+    }
+    debug!("Updating activity of {} users", pending.len());
+
+    let collection = User::collection(database());
+    for (user_id, seen) in pending {
+        let update = doc! {
+            "$set": {
+                "username": seen.username,
+                "discriminator": seen.discriminator as i32,
+                "last_seen": seen.last_seen,
+            },
+            "$setOnInsert": {
+                "first_use": seen.last_seen,
+            },
+        };
+        let options = UpdateOptions::builder().upsert(true).build();
+        let filter = doc! { "discord_id": user_id.0 };
+
+        if let Err(err) = collection.update_one(filter, update, options).await {
+            warn!("Failed to update activity of user {}: {:?}", user_id, err);
+        }
+    }
+}
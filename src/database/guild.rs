@@ -1,18 +1,115 @@
 use serde::{Deserialize, Serialize};
+use serenity::model::id::GuildId;
 use wither::bson::doc;
 use wither::bson::oid::ObjectId;
 use wither::prelude::*;
 
+use super::timestamp;
 use crate::lang::Language;
 
 #[derive(Debug, Model, Serialize, Deserialize)]
+#[model(index(keys = r#"doc!{"discord_id": 1}"#, options = r#"doc!{"unique": true}"#))]
 pub struct Guild {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
 
     pub discord_id: u64,
 
-    pub lang: Language,
+    /// Language chosen for the guild, unset to follow its preferred locale.
+    #[serde(default)]
+    pub lang: Option<Language>,
+
+    /// Locale set in the community settings of the guild, updated when the
+    /// bot receives the guild.
+    #[serde(default)]
+    pub preferred_locale: Option<String>,
 
     pub join_date: i64,
+
+    /// When the bot was removed from the guild, unset while it is a member.
+    #[serde(default)]
+    pub left_date: Option<i64>,
+
+    /// Custom command prefixes, the bot prefix is used when empty.
+    #[serde(default)]
+    pub prefixes: Vec<String>,
+
+    #[serde(default)]
+    pub dj_role: Option<u64>,
+
+    /// Volume percentage the player starts with.
+    #[serde(default = "default_volume")]
+    pub default_volume: u8,
+
+    /// Percentage of listeners that must vote to skip a song.
+    #[serde(default = "default_vote_skip_threshold")]
+    pub vote_skip_threshold: u8,
+
+    /// Channel for player messages, instead of the channel where music was
+    /// requested.
+    #[serde(default)]
+    pub announcement_channel: Option<u64>,
+
+    /// Channels where music commands can be used, any channel when empty.
+    #[serde(default)]
+    pub music_channels: Vec<u64>,
+
+    #[serde(default)]
+    pub max_queue_length: Option<u32>,
+
+    /// Longest track that can be queued, in seconds.
+    #[serde(default)]
+    pub max_track_duration: Option<u64>,
+
+    /// Channel where every message is played as a song request.
+    #[serde(default)]
+    pub request_channel: Option<u64>,
+
+    /// Dashboard message of the request channel, showing the player.
+    #[serde(default)]
+    pub request_message: Option<u64>,
+
+    /// Emoji that queues the links of a message when reacting with it.
+    #[serde(default)]
+    pub play_reaction: Option<String>,
+}
+
+fn default_volume() -> u8 {
+    100
+}
+
+fn default_vote_skip_threshold() -> u8 {
+    50
+}
+
+impl Guild {
+    pub fn new(guild_id: GuildId) -> Self {
+        Self {
+            id: None,
+            discord_id: guild_id.0,
+            lang: None,
+            preferred_locale: None,
+            join_date: timestamp(),
+            left_date: None,
+            prefixes: Vec::new(),
+            dj_role: None,
+            default_volume: default_volume(),
+            vote_skip_threshold: default_vote_skip_threshold(),
+            announcement_channel: None,
+            music_channels: Vec::new(),
+            max_queue_length: None,
+            max_track_duration: None,
+            request_channel: None,
+            request_message: None,
+            play_reaction: None,
+        }
+    }
+
+    /// Language for messages of the guild: the one chosen for it, or the one
+    /// matching its preferred locale.
+    pub fn language(&self) -> Language {
+        self.lang
+            .or_else(|| self.preferred_locale.as_deref()?.parse().ok())
+            .unwrap_or_default()
+    }
 }
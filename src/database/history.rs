@@ -0,0 +1,424 @@
+use std::time::Duration;
+
+use futures::stream::TryStreamExt;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serenity::model::id::{GuildId, UserId};
+use wither::bson::oid::ObjectId;
+use wither::bson::{self, doc, Bson, Document};
+use wither::mongodb::options::FindOptions;
+use wither::prelude::*;
+
+use super::{database, timestamp};
+use crate::audio::source::MediaResource;
+
+/// Start of the link of a YouTube video, followed by its id.
+const YOUTUBE_WATCH_URL: &str = "https://www.youtube.com/watch?v=";
+
+/// A song played in a guild, recorded when it stops playing.
+#[derive(Debug, Clone, Model, Serialize, Deserialize)]
+#[model(
+    collection_name = "history",
+    index(keys = r#"doc!{"guild_id": 1, "started_at": -1}"#),
+    index(keys = r#"doc!{"requester": 1, "started_at": -1}"#)
+)]
+pub struct PlayedSong {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    pub guild_id: u64,
+
+    pub requester: Option<u64>,
+
+    pub title: String,
+
+    /// Link to play the song again. Songs played again have the same link,
+    /// so statistics group songs by it.
+    pub url: Option<String>,
+
+    /// Length of the song in seconds, unknown for live streams.
+    pub duration: Option<f64>,
+
+    pub started_at: i64,
+
+    pub ended_at: i64,
+
+    /// Seconds the song was listened to, less than its duration when it was
+    /// skipped.
+    pub listened: i64,
+}
+
+impl PlayedSong {
+    pub fn new(
+        guild_id: GuildId, media: &MediaResource, started_at: i64, listened: Duration,
+    ) -> Self {
+        Self {
+            id: None,
+            guild_id: guild_id.0,
+            requester: media.requester.map(|user| user.0),
+            title: media.title().unwrap_or_default(),
+            url: media_url(media),
+            duration: media.length().map(|duration| duration.as_secs_f64()),
+            started_at,
+            ended_at: timestamp(),
+            listened: listened.as_secs() as i64,
+        }
+    }
+
+    pub fn requester(&self) -> Option<UserId> {
+        self.requester.map(UserId)
+    }
+}
+
+/// Link to play some media again. Songs of YouTube playlists only have the
+/// id of the video as their `url`, so their link is built from it. Ids of
+/// other sites don't make YouTube links.
+fn media_url(media: &MediaResource) -> Option<String> {
+    let link = media
+        .webpage_url
+        .iter()
+        .chain(&media.url)
+        .find(|url| url.starts_with("http"));
+    match (link, &media.id) {
+        (Some(link), _) => Some(link.clone()),
+        (None, Some(id)) if media.is_youtube() => Some(format!("{}{}", YOUTUBE_WATCH_URL, id)),
+        _ => None,
+    }
+}
+
+/// Store a played song.
+pub async fn record(mut song: PlayedSong) -> wither::Result<()> {
+    song.save(database(), None).await
+}
+
+/// Songs played in a guild, the most recent first, skipping the `skip` most
+/// recent ones.
+pub async fn recent(guild_id: GuildId, skip: u64, limit: i64) -> wither::Result<Vec<PlayedSong>> {
+    let options = FindOptions::builder()
+        .sort(doc! { "started_at": -1 })
+        .skip(skip)
+        .limit(limit)
+        .build();
+    let filter = doc! { "guild_id": guild_id.0 };
+    PlayedSong::find(database(), filter, options)
+        .await?
+        .try_collect()
+        .await
+}
+
+/// Songs shown in the rankings of listening statistics.
+const TOP: i64 = 10;
+
+/// Songs shown in the yearly summary.
+const WRAPPED_TOP: i64 = 5;
+
+/// Seconds in a day.
+const DAY: i64 = 86_400;
+
+/// Whose played songs listening statistics count.
+#[derive(Debug, Clone, Copy)]
+pub enum Scope {
+    /// Songs played in a guild.
+    Guild(GuildId),
+    /// Songs requested by a user in every guild.
+    User(UserId),
+}
+
+impl Scope {
+    fn filter(self) -> Document {
+        match self {
+            Scope::Guild(guild_id) => doc! { "guild_id": guild_id.0 },
+            Scope::User(user_id) => doc! { "requester": user_id.0 },
+        }
+    }
+}
+
+/// Time window of listening statistics, up to now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    Week,
+    Month,
+    All,
+}
+
+impl Window {
+    pub const ALL: [Window; 3] = [Window::Week, Window::Month, Window::All];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Window::Week => "week",
+            Window::Month => "month",
+            Window::All => "all",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|window| window.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Start of the window, or `None` when it covers all the history.
+    fn start(self) -> Option<i64> {
+        match self {
+            Window::Week => Some(timestamp() - 7 * DAY),
+            Window::Month => Some(timestamp() - 30 * DAY),
+            Window::All => None,
+        }
+    }
+}
+
+/// UNIX time of the start of a year, in UTC.
+fn year_start(year: i32) -> i64 {
+    let days_before = |year: i64| 365 * year + year / 4 - year / 100 + year / 400;
+    let year = i64::from(year);
+    (days_before(year - 1) - days_before(1969)) * DAY
+}
+
+/// Current year, in UTC.
+pub fn current_year() -> i32 {
+    let now = timestamp();
+    let mut year = 1970 + (now / (365 * DAY)) as i32;
+    while year_start(year) > now {
+        year -= 1;
+    }
+    while year_start(year + 1) <= now {
+        year += 1;
+    }
+    year
+}
+
+/// A song of the rankings, with how many times it was played.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TopSong {
+    pub title: String,
+    pub url: Option<String>,
+    pub plays: i64,
+    pub listened: i64,
+}
+
+/// A user of the rankings, with how many of their songs were played.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TopRequester {
+    #[serde(rename = "_id")]
+    pub requester: u64,
+    pub plays: i64,
+    pub listened: i64,
+}
+
+impl TopRequester {
+    pub fn user(&self) -> UserId {
+        UserId(self.requester)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct Totals {
+    pub plays: i64,
+    /// Seconds listened in total.
+    pub listened: i64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct Count {
+    count: i64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct Month {
+    #[serde(rename = "_id")]
+    month: u32,
+}
+
+/// Listening statistics over a time window.
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    pub totals: Totals,
+    pub top_songs: Vec<TopSong>,
+    /// Users whose songs were played the most, only for guilds.
+    pub top_requesters: Vec<TopRequester>,
+}
+
+/// Summary of the songs a user listened to in a year.
+#[derive(Debug, Clone, Default)]
+pub struct Wrapped {
+    pub totals: Totals,
+    pub top_songs: Vec<TopSong>,
+    /// Different songs played.
+    pub songs: i64,
+    /// Days with a played song, in UTC.
+    pub days: i64,
+    pub guilds: i64,
+    /// Month with the most time listened, from 1 to 12.
+    pub top_month: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatsFacets {
+    totals: Vec<Totals>,
+    top_songs: Vec<TopSong>,
+    #[serde(default)]
+    top_requesters: Vec<TopRequester>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WrappedFacets {
+    totals: Vec<Totals>,
+    top_songs: Vec<TopSong>,
+    songs: Vec<Count>,
+    days: Vec<Count>,
+    guilds: Vec<Count>,
+    top_month: Vec<Month>,
+}
+
+/// Played songs in a scope that started between `from` and `to`.
+fn match_stage(scope: Scope, from: Option<i64>, to: Option<i64>) -> Document {
+    let mut filter = scope.filter();
+    let mut started_at = Document::new();
+    if let Some(from) = from {
+        started_at.insert("$gte", from);
+    }
+    if let Some(to) = to {
+        started_at.insert("$lt", to);
+    }
+    if !started_at.is_empty() {
+        filter.insert("started_at", started_at);
+    }
+    doc! { "$match": filter }
+}
+
+fn totals_facet() -> Vec<Document> {
+    vec![doc! {
+        "$group": {
+            "_id": null,
+            "plays": { "$sum": 1 },
+            "listened": { "$sum": "$listened" },
+        }
+    }]
+}
+
+/// What tells songs apart: their link, or their title when they have none.
+fn song_key() -> Document {
+    doc! { "$ifNull": ["$url", "$title"] }
+}
+
+/// Most played songs.
+fn top_songs_facet(limit: i64) -> Vec<Document> {
+    vec![
+        doc! {
+            "$group": {
+                "_id": song_key(),
+                "title": { "$first": "$title" },
+                "url": { "$first": "$url" },
+                "plays": { "$sum": 1 },
+                "listened": { "$sum": "$listened" },
+            }
+        },
+        doc! { "$sort": { "plays": -1, "listened": -1 } },
+        doc! { "$limit": limit },
+    ]
+}
+
+fn top_requesters_facet(limit: i64) -> Vec<Document> {
+    vec![
+        doc! { "$match": { "requester": { "$ne": null } } },
+        doc! {
+            "$group": {
+                "_id": "$requester",
+                "plays": { "$sum": 1 },
+                "listened": { "$sum": "$listened" },
+            }
+        },
+        doc! { "$sort": { "plays": -1, "listened": -1 } },
+        doc! { "$limit": limit },
+    ]
+}
+
+/// Number of different values of an expression.
+fn distinct_facet(expression: impl Into<Bson>) -> Vec<Document> {
+    vec![
+        doc! { "$group": { "_id": expression.into() } },
+        doc! { "$count": "count" },
+    ]
+}
+
+/// Run a pipeline of a `$match` stage followed by a `$facet` stage, which
+/// gives a single document with the result of every facet.
+async fn run_facets<T>(filter: Document, facets: Document) -> wither::Result<Option<T>>
+where
+    T: DeserializeOwned, {
+    let pipeline = vec![filter, doc! { "$facet": facets }];
+    let result = PlayedSong::collection(database())
+        .aggregate(pipeline, None)
+        .await?
+        .try_next()
+        .await?;
+    match result {
+        Some(result) => Ok(Some(bson::from_document(result)?)),
+        None => Ok(None),
+    }
+}
+
+/// Totals and rankings of the songs played in a scope over a window.
+pub async fn stats(scope: Scope, window: Window) -> wither::Result<Stats> {
+    let mut facets = doc! {
+        "totals": totals_facet(),
+        "top_songs": top_songs_facet(TOP),
+    };
+    if let Scope::Guild(_) = scope {
+        facets.insert("top_requesters", top_requesters_facet(TOP));
+    }
+
+    let filter = match_stage(scope, window.start(), None);
+    let facets: StatsFacets = match run_facets(filter, facets).await? {
+        Some(facets) => facets,
+        None => return Ok(Stats::default()),
+    };
+    Ok(Stats {
+        totals: facets.totals.into_iter().next().unwrap_or_default(),
+        top_songs: facets.top_songs,
+        top_requesters: facets.top_requesters,
+    })
+}
+
+/// Summary of the songs requested by a user during a year.
+pub async fn wrapped(user_id: UserId, year: i32) -> wither::Result<Wrapped> {
+    let started_at = doc! { "$toDate": { "$multiply": ["$started_at", 1000] } };
+    let facets = doc! {
+        "totals": totals_facet(),
+        "top_songs": top_songs_facet(WRAPPED_TOP),
+        "songs": distinct_facet(song_key()),
+        "days": distinct_facet(doc! { "$floor": { "$divide": ["$started_at", DAY] } }),
+        "guilds": distinct_facet("$guild_id"),
+        "top_month": [
+            {
+                "$group": {
+                    "_id": { "$month": started_at },
+                    "listened": { "$sum": "$listened" },
+                }
+            },
+            { "$sort": { "listened": -1 } },
+            { "$limit": 1 },
+        ],
+    };
+
+    let filter = match_stage(
+        Scope::User(user_id),
+        Some(year_start(year)),
+        Some(year_start(year + 1)),
+    );
+    let facets: WrappedFacets = match run_facets(filter, facets).await? {
+        Some(facets) => facets,
+        None => return Ok(Wrapped::default()),
+    };
+    let count = |counts: Vec<Count>| counts.first().map_or(0, |count| count.count);
+    Ok(Wrapped {
+        totals: facets.totals.into_iter().next().unwrap_or_default(),
+        top_songs: facets.top_songs,
+        songs: count(facets.songs),
+        days: count(facets.days),
+        guilds: count(facets.guilds),
+        top_month: facets.top_month.first().map(|month| month.month),
+    })
+}
@@ -0,0 +1,27 @@
+use lyrics::SearchResult;
+use serde::{Deserialize, Serialize};
+use wither::bson::doc;
+use wither::bson::oid::ObjectId;
+use wither::prelude::*;
+
+/// Lyrics found for a song, keyed by its normalized artist and title.
+#[derive(Debug, Model, Serialize, Deserialize)]
+#[model(
+    collection_name = "lyrics",
+    index(
+        keys = r#"doc!{"artist": 1, "title": 1}"#,
+        options = r#"doc!{"unique": true}"#
+    )
+)]
+pub struct LyricsCache {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    pub artist: String,
+
+    pub title: String,
+
+    pub result: SearchResult,
+
+    pub cached_at: i64,
+}
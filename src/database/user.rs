@@ -1,10 +1,26 @@
 use serde::{Deserialize, Serialize};
+use serenity::model::user::User as DiscordUser;
 use wither::bson::doc;
 use wither::bson::oid::ObjectId;
 use wither::prelude::*;
 
+use super::timestamp;
+use crate::audio::source::SearchSource;
 use crate::lang::Language;
 
+/// How command answers are shown to a user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplyStyle {
+    Embed,
+    Compact,
+}
+
+impl Default for ReplyStyle {
+    fn default() -> Self {
+        ReplyStyle::Embed
+    }
+}
+
 #[derive(Debug, Model, Serialize, Deserialize)]
 #[model(index(keys = r#"doc!{"discord_id": 1}"#, options = r#"doc!{"unique": true}"#))]
 pub struct User {
@@ -17,9 +33,39 @@ pub struct User {
 
     pub discriminator: u16,
 
-    pub lang: Language,
+    /// Language chosen by the user, the guild language is used when unset.
+    pub lang: Option<Language>,
 
     pub first_use: i64,
 
     pub last_seen: i64,
+
+    /// Send a direct message when a song requested by the user starts.
+    #[serde(default)]
+    pub dm_notifications: bool,
+
+    #[serde(default)]
+    pub reply_style: ReplyStyle,
+
+    /// Where `play` searches songs requested by name.
+    #[serde(default)]
+    pub search_source: SearchSource,
+}
+
+impl User {
+    pub fn new(user: &DiscordUser) -> Self {
+        let now = timestamp();
+        Self {
+            id: None,
+            discord_id: user.id.0,
+            username: user.name.clone(),
+            discriminator: user.discriminator,
+            lang: None,
+            first_use: now,
+            last_seen: now,
+            dm_notifications: false,
+            reply_style: ReplyStyle::default(),
+            search_source: SearchSource::default(),
+        }
+    }
 }
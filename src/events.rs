@@ -2,15 +2,67 @@ use serenity::async_trait;
 use serenity::client::{Context, EventHandler};
 use serenity::model::gateway::Ready;
 use serenity::model::prelude::*;
-use tracing::info;
+use tracing::{info, warn};
 
-pub struct Handler;
+use crate::audio::player;
+use crate::commands::{play, slash};
+use crate::database;
+use crate::prefixes::default_prefix;
+
+pub struct Handler {
+    /// Guild where slash commands are registered, instead of globally.
+    pub slash_commands_guild: Option<GuildId>,
+}
 
 #[async_trait]
 impl EventHandler for Handler {
     async fn ready(&self, ctx: Context, ready: Ready) {
-        ctx.set_presence(Some(Activity::playing(".help")), OnlineStatus::DoNotDisturb)
+        let activity = Activity::playing(&format!("{}help", default_prefix()));
+        ctx.set_presence(Some(activity), OnlineStatus::DoNotDisturb)
             .await;
         info!("{} connected!", ready.user.name);
+
+        if let Err(err) = slash::register(&ctx, self.slash_commands_guild).await {
+            warn!("Failed to register slash commands: {:?}", err);
+        }
+    }
+
+    async fn guild_create(&self, _ctx: Context, guild: Guild, _is_new: bool) {
+        if let Err(err) = database::join_guild(&guild).await {
+            warn!("Failed to save guild {}: {:?}", guild.id, err);
+        }
+    }
+
+    async fn guild_delete(
+        &self, _ctx: Context, incomplete: GuildUnavailable, _full: Option<Guild>,
+    ) {
+        // Guilds also become unavailable during outages, the bot was only
+        // removed when they are still available
+        if incomplete.unavailable {
+            return;
+        }
+        if let Err(err) = database::leave_guild(incomplete.id).await {
+            warn!("Failed to mark guild {} as left: {:?}", incomplete.id, err);
+        }
+    }
+
+    async fn message(&self, ctx: Context, msg: Message) {
+        play::handle_request(&ctx, &msg).await;
+    }
+
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        play::handle_reaction(&ctx, &reaction).await;
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        match interaction {
+            Interaction::ApplicationCommand(command) => slash::handle(&ctx, &command).await,
+            Interaction::MessageComponent(component) => {
+                if let Err(err) = player::handle_component(&ctx, &component).await {
+                    warn!("Failed to handle player button: {:?}", err);
+                }
+            },
+            _ => {},
+        }
     }
 }
@@ -1,64 +1,114 @@
+use std::sync::Arc;
+
 use serde_json::json;
 use serenity::framework::standard::macros::hook;
-use serenity::framework::standard::CommandError;
+use serenity::framework::standard::{CommandError, DispatchError, Reason};
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 use serenity::utils::Colour;
 use tracing::error;
 
+use crate::audio::queue::MediaQueueError;
+use crate::checks::RATE_LIMITED;
 use crate::constants::ERROR_MARK;
-use crate::database::get_language;
+use crate::database::{activity, get_language};
+use crate::invocation::Invocation;
+use crate::lang::LocalizedError;
+use crate::prefixes::{self, default_prefix, find_prefix};
+
+#[hook]
+pub async fn before_hook(_ctx: &Context, msg: &Message, _cmd_name: &str) -> bool {
+    activity::record(&msg.author).await;
+    true
+}
 
 #[hook]
 pub async fn after_hook(
     ctx: &Context, msg: &Message, cmd_name: &str, error: Result<(), CommandError>,
 ) {
     if let Err(why) = error {
-        error!("Error in command '{}': {:?}", cmd_name, why);
+        report_error(ctx, &msg.into(), cmd_name, why).await;
+    }
+}
 
-        let lang = get_language(msg.author.id, msg.guild_id).await;
-        let title = lang.get("command.error.title");
+/// Log a failed command and show its error to the caller. Returns the error
+/// message, unless sending it failed.
+pub async fn report_error(
+    ctx: &Context, invocation: &Invocation<'_>, cmd_name: &str, why: CommandError,
+) -> Option<Message> {
+    error!("Error in command '{}': {:?}", cmd_name, why);
 
-        let text = lang
+    let lang = invocation.language().await;
+    // Queue errors have their own messages for users
+    let queue_error = why
+        .downcast_ref::<MediaQueueError>()
+        .map(LocalizedError::from);
+    let localized = why.downcast_ref::<LocalizedError>();
+    let text = match localized.or(queue_error.as_ref()) {
+        Some(err) => err.translate(lang),
+        None => lang
             .translate(
                 "command.error.internal",
                 json!({ "error": why.to_string() }),
             )
-            .unwrap_or_else(|_| "Command failed".into());
+            .unwrap_or_else(|_| "Command failed".into()),
+    };
 
-        let err = msg
-            .channel_id
-            .send_message(&ctx.http, |m| {
-                m.reference_message(msg);
-                m.allowed_mentions(|f| f.replied_user(false));
-                m.embed(|e| {
-                    e.author(|a| {
-                        a.name(&msg.author.name);
-                        a.icon_url(
-                            msg.author
-                                .avatar_url()
-                                .unwrap_or_else(|| msg.author.default_avatar_url()),
-                        );
-                        a
-                    });
-                    e.color(Colour::DARK_RED);
-                    e.thumbnail(ERROR_MARK);
-                    e.title(title);
-                    e.description(text);
-                    e
-                });
-                m
-            })
-            .await;
+    send_error(ctx, invocation, text).await
+}
 
-        if let Err(why) = err {
-            error!("Error sending a message: {:?}", why);
+#[hook]
+pub async fn dispatch_error(ctx: &Context, msg: &Message, error: DispatchError) {
+    if let DispatchError::CheckFailed(_, reason) = error {
+        match reason {
+            Reason::User(reason) if reason == RATE_LIMITED => delay_action(ctx, msg).await,
+            Reason::User(reason) | Reason::UserAndLog { user: reason, .. } => {
+                // Checks give translation keys as reasons
+                let lang = get_language(msg.author.id, msg.guild_id, None).await;
+                send_error(ctx, &msg.into(), lang.get(reason)).await;
+            },
+            _ => {},
         }
     }
 }
 
+pub async fn send_error(
+    ctx: &Context, invocation: &Invocation<'_>, text: String,
+) -> Option<Message> {
+    let lang = invocation.language().await;
+    let title = lang.get("command.error.title");
+
+    let err = invocation
+        .send_embed(ctx, |e| {
+            invocation.embed_author(e);
+            e.color(Colour::DARK_RED);
+            e.thumbnail(ERROR_MARK);
+            e.title(title);
+            e.description(text);
+            e
+        })
+        .await;
+
+    match err {
+        Ok(message) => Some(message),
+        Err(why) => {
+            error!("Error sending a message: {:?}", why);
+            None
+        },
+    }
+}
+
+/// Pick the prefix of a message among the prefixes of its guild.
 #[hook]
-pub async fn delay_action(ctx: &Context, msg: &Message) {
+pub async fn dynamic_prefix(_ctx: &Context, msg: &Message) -> Option<String> {
+    let prefixes = match msg.guild_id {
+        Some(guild_id) => prefixes::get_prefixes(guild_id).await,
+        None => Arc::new(vec![default_prefix().to_string()]),
+    };
+    find_prefix(&prefixes, &msg.content).map(String::from)
+}
+
+async fn delay_action(ctx: &Context, msg: &Message) {
     // You may want to handle a Discord rate limit if this fails.
     if let Err(why) = msg.react(ctx, '⏱').await {
         error!("Failed to insert delay reaction: {:?}", why);
@@ -0,0 +1,153 @@
+use std::fmt::Display;
+
+use serenity::builder::{CreateComponents, CreateEmbed};
+use serenity::client::Context;
+use serenity::model::channel::Message;
+use serenity::model::guild::Guild;
+use serenity::model::id::{ChannelId, GuildId};
+use serenity::model::interactions::application_command::ApplicationCommandInteraction;
+use serenity::model::user::User;
+
+use crate::database::get_language;
+use crate::lang::Language;
+
+/// Where a command was invoked from: a prefixed chat message, a slash
+/// command or a reaction shortcut. Commands take this instead of a
+/// `Message`, so the same code answers all of them.
+#[derive(Debug, Clone, Copy)]
+pub enum Invocation<'a> {
+    Message(&'a Message),
+    Interaction(&'a ApplicationCommandInteraction),
+    /// A user reacted to a message, answers reply to that message.
+    Reaction(&'a Message, &'a User),
+}
+
+impl<'a> From<&'a Message> for Invocation<'a> {
+    fn from(msg: &'a Message) -> Self {
+        Invocation::Message(msg)
+    }
+}
+
+impl<'a> From<&'a ApplicationCommandInteraction> for Invocation<'a> {
+    fn from(interaction: &'a ApplicationCommandInteraction) -> Self {
+        Invocation::Interaction(interaction)
+    }
+}
+
+impl<'a> Invocation<'a> {
+    pub fn author(&self) -> &'a User {
+        match self {
+            Invocation::Message(msg) => &msg.author,
+            Invocation::Interaction(interaction) => &interaction.user,
+            Invocation::Reaction(_, user) => user,
+        }
+    }
+
+    pub fn guild_id(&self) -> Option<GuildId> {
+        match self {
+            Invocation::Message(msg) | Invocation::Reaction(msg, _) => msg.guild_id,
+            Invocation::Interaction(interaction) => interaction.guild_id,
+        }
+    }
+
+    pub fn channel_id(&self) -> ChannelId {
+        match self {
+            Invocation::Message(msg) | Invocation::Reaction(msg, _) => msg.channel_id,
+            Invocation::Interaction(interaction) => interaction.channel_id,
+        }
+    }
+
+    /// Locale of the Discord client of the author, only sent with slash
+    /// commands.
+    pub fn locale(&self) -> Option<&'a str> {
+        match self {
+            Invocation::Message(_) | Invocation::Reaction(..) => None,
+            Invocation::Interaction(interaction) => Some(&interaction.locale),
+        }
+    }
+
+    /// Language to answer the author in.
+    pub async fn language(&self) -> Language {
+        get_language(self.author().id, self.guild_id(), self.locale()).await
+    }
+
+    pub async fn guild(&self, ctx: &Context) -> Option<Guild> {
+        self.guild_id()?.to_guild_cached(&ctx.cache).await
+    }
+
+    /// Answer the invocation with an embed. Slash commands are deferred when
+    /// received, so their answers are sent as follow-up messages.
+    pub async fn send_embed<F>(&self, ctx: &Context, f: F) -> Result<Message, serenity::Error>
+    where
+        F: FnOnce(&mut CreateEmbed) -> &mut CreateEmbed, {
+        self.send_embed_with_components(ctx, f, |c| c).await
+    }
+
+    /// Answer the invocation with an embed and components, like buttons.
+    pub async fn send_embed_with_components<F, C>(
+        &self, ctx: &Context, f: F, components: C,
+    ) -> Result<Message, serenity::Error>
+    where
+        F: FnOnce(&mut CreateEmbed) -> &mut CreateEmbed,
+        C: FnOnce(&mut CreateComponents) -> &mut CreateComponents, {
+        match self {
+            Invocation::Message(msg) | Invocation::Reaction(msg, _) => {
+                msg.channel_id
+                    .send_message(&ctx.http, |m| {
+                        m.reference_message(*msg);
+                        m.allowed_mentions(|f| f.replied_user(false));
+                        m.embed(f);
+                        m.components(components);
+                        m
+                    })
+                    .await
+            },
+            Invocation::Interaction(interaction) => {
+                interaction
+                    .create_followup_message(&ctx.http, |m| {
+                        m.create_embed(f);
+                        m.components(components);
+                        m
+                    })
+                    .await
+            },
+        }
+    }
+
+    /// Answer the invocation with plain text.
+    pub async fn reply<S: Display>(
+        &self, ctx: &Context, content: S,
+    ) -> Result<Message, serenity::Error> {
+        match self {
+            Invocation::Message(msg) => msg.reply(ctx, content).await,
+            Invocation::Reaction(msg, _) => {
+                msg.channel_id
+                    .send_message(&ctx.http, |m| {
+                        m.reference_message(*msg);
+                        m.allowed_mentions(|f| f.replied_user(false));
+                        m.content(content)
+                    })
+                    .await
+            },
+            Invocation::Interaction(interaction) => {
+                interaction
+                    .create_followup_message(&ctx.http, |m| m.content(content))
+                    .await
+            },
+        }
+    }
+
+    /// Embed author block with the invoking user, used by most answers.
+    pub fn embed_author<'e>(&self, e: &'e mut CreateEmbed) -> &'e mut CreateEmbed {
+        let author = self.author();
+        e.author(|a| {
+            a.name(&author.name);
+            a.icon_url(
+                author
+                    .avatar_url()
+                    .unwrap_or_else(|| author.default_avatar_url()),
+            );
+            a
+        })
+    }
+}
@@ -1,126 +1,251 @@
 use std::collections::HashMap;
 use std::fmt::Display;
-use std::fs::File;
-use std::io::BufReader;
-use std::lazy::SyncLazy;
+use std::str::FromStr;
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
-use strfmt::strfmt;
 use thiserror::Error;
 
+use self::message::{Message, SyntaxError};
+use self::registry::locales;
+
+mod locale;
+mod message;
+pub mod registry;
+
 #[derive(Debug, Error)]
 pub enum TranslationError {
-    #[error("Language was not initialized {0}")]
-    LangNotInitialized(Language),
-
     #[error("String '{1}' not found in {0}")]
     StringNotFound(Language, String),
 
-    #[error("Failed to decode translation")]
+    #[error("Failed to decode translation: {0}")]
     Json(#[from] serde_json::Error),
 
-    #[error("Failed to format translation")]
-    Format(#[from] strfmt::FmtError),
+    #[error("Missing variable '{0}' to format translation")]
+    MissingVariable(String),
+
+    #[error("Invalid translation '{0}': {1}")]
+    Syntax(String, SyntaxError),
 
-    #[error("IO error")]
+    #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    #[error("Failed to load {0}: {1}")]
+    File(String, Box<TranslationError>),
+
+    #[error("Missing translations for {0}")]
+    MissingLanguage(Language),
+
+    #[error("Unknown language '{0}'")]
+    UnknownLanguage(String),
 }
 
-static TRANSLATIONS: SyncLazy<HashMap<Language, HashMap<String, String>>> = SyncLazy::new(|| {
-    let mut v = HashMap::new();
-    v.insert(
-        Language::English,
-        load_language(Language::English).expect("Failed to load english language"),
-    );
-    v.insert(
-        Language::Spanish,
-        load_language(Language::Spanish).expect("Failed to load spanish language"),
-    );
-    v
-});
-
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
-pub enum Language {
-    English,
-    Spanish,
+/// Error meant for users, translated to their language when reported. Its
+/// message is the translation key, with `data` filling the placeholders.
+#[derive(Debug, Error)]
+#[error("{key}")]
+pub struct LocalizedError {
+    pub key: &'static str,
+    pub data: Value,
+}
+
+impl LocalizedError {
+    pub fn new(key: &'static str) -> Self {
+        Self::with(key, Value::Null)
+    }
+
+    pub fn with(key: &'static str, data: Value) -> Self {
+        Self { key, data }
+    }
+
+    pub fn translate(&self, lang: Language) -> String {
+        match self.data {
+            Value::Null => lang.get(self.key),
+            ref data => lang
+                .translate(self.key, data.clone())
+                .unwrap_or_else(|_| lang.get(self.key)),
+        }
+    }
 }
 
+/// Names stored by versions where `Language` was an enum, with their locale
+/// codes. They are still written, so older versions keep reading them.
+const LEGACY_NAMES: &[(&str, &str)] = &[("English", "en"), ("Spanish", "es")];
+
+/// A language, identified by its locale code like `en` or `es-MX`.
+/// Languages are discovered from the translation files in
+/// [`registry::I18N_DIR`].
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Language(&'static str);
+
 impl Default for Language {
     fn default() -> Self {
-        Language::English
+        Language("en")
     }
 }
 
-impl From<&Language> for &'static str {
-    fn from(lang: &Language) -> &'static str {
-        match lang {
-            Language::English => "en",
-            Language::Spanish => "es",
+impl FromStr for Language {
+    type Err = TranslationError;
+
+    /// Find a loaded language by its code, its name or its legacy name. Codes
+    /// of unknown regions resolve to their base language, `es-AR` to `es`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input = s.trim().to_lowercase();
+        let code = registry::normalize(&input);
+        let legacy = LEGACY_NAMES
+            .iter()
+            .find(|(name, _)| name.to_lowercase() == input)
+            .map(|(_, code)| *code);
+
+        let found = locales().iter().find_map(|(lang, locale)| {
+            let matches =
+                lang.0 == code || locale.name.to_lowercase() == input || legacy == Some(lang.0);
+            matches.then(|| *lang)
+        });
+        if let Some(lang) = found {
+            return Ok(lang);
+        }
+
+        let mut parent = code.as_str();
+        while let Some((prefix, _)) = parent.rsplit_once('-') {
+            if let Some(lang) = Language::loaded(prefix) {
+                return Ok(lang);
+            }
+            parent = prefix;
         }
+        Err(TranslationError::UnknownLanguage(s.to_string()))
     }
 }
 
 impl Display for Language {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let string: &str = self.into();
-        write!(f, "{}", string)
+        f.write_str(self.0)
+    }
+}
+
+impl Serialize for Language {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer, {
+        let name = LEGACY_NAMES
+            .iter()
+            .find(|(_, code)| *code == self.0)
+            .map_or(self.0, |(name, _)| *name);
+        serializer.serialize_str(name)
+    }
+}
+
+impl<'de> Deserialize<'de> for Language {
+    /// Read a legacy name or a locale code. Languages without translations
+    /// are kept, and fall back to the default language when used.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>, {
+        let value = String::deserialize(deserializer)?;
+        let code = LEGACY_NAMES
+            .iter()
+            .find(|(name, _)| *name == value)
+            .map_or(value.as_str(), |(_, code)| *code);
+        Ok(Language::from_code(code))
     }
 }
 
 impl Language {
-    pub fn get_default<S>(key: S) -> Result<&'static str, TranslationError>
+    fn from_code(code: &str) -> Self {
+        Language(registry::intern(&registry::normalize(code)))
+    }
+
+    /// Loaded language with a normalized code. Unlike [`Language::from_code`],
+    /// codes without translations are not interned.
+    fn loaded(code: &str) -> Option<Self> {
+        locales().keys().copied().find(|lang| lang.0 == code)
+    }
+
+    /// Language subtag of the code, like `es` for `es-MX`.
+    pub fn base(self) -> &'static str {
+        self.0.split('-').next().unwrap_or(self.0)
+    }
+
+    /// Every language with translations, sorted by code.
+    pub fn all() -> Vec<Language> {
+        let mut languages: Vec<Language> = locales().keys().copied().collect();
+        languages.sort();
+        languages
+    }
+
+    /// Name of the language in itself, like `Español`.
+    pub fn name(self) -> String {
+        match locales().get(&self) {
+            Some(locale) => locale.name.clone(),
+            None => self.0.to_string(),
+        }
+    }
+
+    pub fn is_loaded(self) -> bool {
+        locales().contains_key(&self)
+    }
+
+    /// Languages searched for a message, from the most specific:
+    /// `es-MX`, `es` and then the default language.
+    fn fallbacks(self) -> Vec<Language> {
+        let mut chain = vec![self];
+        let mut code = self.0;
+        while let Some((parent, _)) = code.rsplit_once('-') {
+            chain.extend(Language::loaded(parent));
+            code = parent;
+        }
+        if !chain.contains(&Language::default()) {
+            chain.push(Language::default());
+        }
+        chain
+    }
+
+    pub fn get_default<S>(key: S) -> Result<String, TranslationError>
     where
         S: AsRef<str>, {
         Language::default().get_option::<S>(key)
     }
 
-    pub fn get_option<S>(self, key: S) -> Result<&'static str, TranslationError>
+    pub fn get_option<S>(self, key: S) -> Result<String, TranslationError>
     where
         S: AsRef<str>, {
-        let out = TRANSLATIONS
+        locales()
             .get(&self)
-            .ok_or(TranslationError::LangNotInitialized(self))?
-            .get(key.as_ref())
-            .ok_or_else(|| TranslationError::StringNotFound(self, String::from(key.as_ref())))?;
-        Ok(&out[..])
+            .and_then(|locale| locale.messages.get(key.as_ref()))
+            .map(|message| message.text().to_string())
+            .ok_or_else(|| TranslationError::StringNotFound(self, String::from(key.as_ref())))
     }
 
-    pub fn get<'a, S>(self, key: S) -> &'a str
+    /// Use the message of a key with the language it was found in, following
+    /// the fallback chain of the language.
+    fn with_message<T, F>(self, key: &str, f: F) -> Option<T>
     where
-        S: Into<&'a str>, {
-        let key = key.into();
-        self.get_option(&key)
-            .unwrap_or_else(|_| Language::get_default(&key).unwrap_or(key))
+        F: FnOnce(Language, &Message) -> T, {
+        let locales = locales();
+        let (lang, message) = self.fallbacks().into_iter().find_map(|lang| {
+            let message = locales.get(&lang)?.messages.get(key)?;
+            Some((lang, message))
+        })?;
+        Some(f(lang, message))
     }
 
-    pub fn translate<S>(self, key: S, data: Value) -> Result<String, TranslationError>
+    /// Text of a key in the language or its fallbacks, when any has it.
+    pub fn find(self, key: &str) -> Option<String> {
+        self.with_message(key, |_, message| message.text().to_string())
+    }
+
+    pub fn get<S>(self, key: S) -> String
     where
         S: AsRef<str>, {
-        let vars: HashMap<String, String> = HashMap::<String, Value>::deserialize(data)?
-            .iter()
-            .map(|(k, v)| {
-                let v = match v {
-                    Value::Number(ref v) => format!("{}", v),
-                    Value::String(ref v) => v.clone(),
-                    Value::Null => String::from("Null"),
-                    Value::Bool(v) => format!("{}", v),
-                    Value::Array(v) => format!("{:?}", v),
-                    Value::Object(v) => format!("{:?}", v),
-                };
-                (k.clone(), v)
-            })
-            .collect();
-        let translated = strfmt(&self.get(key.as_ref()), &vars)?;
-        Ok(translated)
+        let key = key.as_ref();
+        self.find(key).unwrap_or_else(|| key.to_string())
     }
-}
-
-fn load_language(lang: Language) -> Result<HashMap<String, String>, TranslationError> {
-    let file = File::open(format!("i18n/{}.json", lang))?;
-    let reader = BufReader::new(file);
 
-    let u = serde_json::from_reader(reader)?;
-
-    Ok(u)
+    pub fn translate<S>(self, key: S, data: Value) -> Result<String, TranslationError>
+    where
+        S: AsRef<str>, {
+        let args = HashMap::<String, Value>::deserialize(data)?;
+        self.with_message(key.as_ref(), |lang, message| message.format(lang, &args))
+            .unwrap_or_else(|| Ok(key.as_ref().to_string()))
+    }
 }
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use serde_json::json;
+
+use super::Language;
+
+/// Decimals shown when formatting numbers, trailing zeros are removed.
+const DECIMALS: usize = 2;
+
+impl Language {
+    /// [CLDR plural category](https://cldr.unicode.org/index/cldr-spec/plural-rules)
+    /// of a number, used to select message variants. Languages without rules
+    /// of their own use the English ones.
+    pub fn plural_category(self, number: f64) -> &'static str {
+        let integer = number.fract() == 0.0;
+        match self.base() {
+            "es" if number.abs() == 1.0 => "one",
+            "es" if integer && number != 0.0 && number % 1_000_000.0 == 0.0 => "many",
+            "es" => "other",
+            _ if integer && number.abs() == 1.0 => "one",
+            _ => "other",
+        }
+    }
+
+    /// Format a number with the separators of the language, like `12,345.6`
+    /// in English and `12.345,6` in Spanish. Numbers are rounded to
+    /// [`DECIMALS`] decimals.
+    pub fn format_number(self, number: f64) -> String {
+        // Spanish only groups numbers with five or more digits
+        let (group, decimal, min_grouping) = match self.base() {
+            "es" => ('.', ',', 5),
+            _ => (',', '.', 4),
+        };
+
+        let text = format!("{:.*}", DECIMALS, number.abs());
+        let (integer, fraction) = match text.split_once('.') {
+            Some((integer, fraction)) => (integer, fraction.trim_end_matches('0')),
+            None => (text.as_str(), ""),
+        };
+
+        let mut out = String::new();
+        if number < 0.0 && (integer, fraction) != ("0", "") {
+            out.push('-');
+        }
+        for (i, c) in integer.chars().enumerate() {
+            let remaining = integer.len() - i;
+            if i > 0 && remaining % 3 == 0 && integer.len() >= min_grouping {
+                out.push(group);
+            }
+            out.push(c);
+        }
+        if !fraction.is_empty() {
+            out.push(decimal);
+            out.push_str(fraction);
+        }
+        out
+    }
+
+    /// Format a duration in words, like `1 hour 30 minutes`.
+    pub fn format_duration(self, duration: Duration) -> String {
+        let secs = duration.as_secs();
+        let units = [
+            ("duration.days", secs / 86_400),
+            ("duration.hours", secs / 3_600 % 24),
+            ("duration.minutes", secs / 60 % 60),
+            ("duration.seconds", secs % 60),
+        ];
+
+        let parts: Vec<String> = units
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(key, count)| {
+                self.translate(key, json!({ "count": count }))
+                    .unwrap_or_else(|_| count.to_string())
+            })
+            .collect();
+        if parts.is_empty() {
+            return self
+                .translate("duration.seconds", json!({ "count": 0 }))
+                .unwrap_or_else(|_| "0".into());
+        }
+        parts.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plural_categories() {
+        let en = Language("en");
+        assert_eq!(en.plural_category(1.0), "one");
+        assert_eq!(en.plural_category(0.0), "other");
+        assert_eq!(en.plural_category(1.5), "other");
+        assert_eq!(en.plural_category(1_000_000.0), "other");
+
+        let es = Language("es-MX");
+        assert_eq!(es.plural_category(1.0), "one");
+        assert_eq!(es.plural_category(2.0), "other");
+        assert_eq!(es.plural_category(0.0), "other");
+        assert_eq!(es.plural_category(1_000_000.0), "many");
+        assert_eq!(es.plural_category(3_000_000.0), "many");
+        assert_eq!(es.plural_category(1_000_001.0), "other");
+    }
+
+    #[test]
+    fn grouping() {
+        let en = Language("en");
+        assert_eq!(en.format_number(123.0), "123");
+        assert_eq!(en.format_number(1234.0), "1,234");
+        assert_eq!(en.format_number(1_234_567.0), "1,234,567");
+        assert_eq!(en.format_number(-12_345.5), "-12,345.5");
+
+        let es = Language("es");
+        assert_eq!(es.format_number(1234.0), "1234");
+        assert_eq!(es.format_number(12_345.0), "12.345");
+        assert_eq!(es.format_number(1_234_567.25), "1.234.567,25");
+    }
+
+    #[test]
+    fn rounding() {
+        let en = Language("en");
+        assert_eq!(en.format_number(0.1 + 0.2), "0.3");
+        assert_eq!(en.format_number(2.0 / 3.0), "0.67");
+        assert_eq!(en.format_number(1.999), "2");
+        assert_eq!(en.format_number(-0.001), "0");
+        assert_eq!(Language("es").format_number(0.5), "0,5");
+    }
+}
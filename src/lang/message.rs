@@ -0,0 +1,503 @@
+//! Translation messages, written in a subset of the [Fluent] syntax:
+//!
+//! - `{ $name }` inserts a variable. Numbers are formatted for the language.
+//! - `{ $count -> [one] song *[other] songs }` picks a variant by the plural
+//!   category of a number, by an exact number like `[0]`, or by the value of a
+//!   string. The variant marked with `*` is used when no other matches.
+//!   Whitespace around variants is trimmed, `{ " " }` keeps it.
+//! - `{ DURATION($secs) }` formats a number of seconds in words, and `{
+//!   NUMBER($value) }` formats a number.
+//! - `{ "text" }` inserts text as is, useful to escape braces.
+//!
+//! [Fluent]: https://projectfluent.org/fluent/guide/
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde_json::Value;
+use thiserror::Error;
+
+use super::{Language, TranslationError};
+
+#[derive(Debug, Error)]
+#[error("{reason} at character {position}")]
+pub struct SyntaxError {
+    position: usize,
+    reason: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    source: String,
+    /// Formatted message when it has no variables.
+    text: Option<String>,
+    elements: Vec<Element>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Element {
+    Text(String),
+    Literal(String),
+    Variable(String),
+    Function(Function, String),
+    Select {
+        selector: String,
+        variants: Vec<Variant>,
+        default: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Duration,
+    Number,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Variant {
+    key: VariantKey,
+    value: Vec<Element>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum VariantKey {
+    Number(f64),
+    Name(String),
+}
+
+impl Message {
+    pub fn parse(source: &str) -> Result<Self, SyntaxError> {
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            position: 0,
+        };
+        let elements = parser.pattern(false)?;
+        if parser.position < parser.chars.len() {
+            return Err(parser.error("Unexpected `}`"));
+        }
+        let text = elements
+            .iter()
+            .map(|element| match element {
+                Element::Text(text) | Element::Literal(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Option<String>>();
+        Ok(Self {
+            source: source.to_string(),
+            text,
+            elements,
+        })
+    }
+
+    /// Text of a message without variables, or the source of the message.
+    pub fn text(&self) -> &str {
+        self.text.as_deref().unwrap_or(&self.source)
+    }
+
+    pub fn format(
+        &self, lang: Language, args: &HashMap<String, Value>,
+    ) -> Result<String, TranslationError> {
+        let mut out = String::new();
+        format_elements(&self.elements, lang, args, &mut out)?;
+        Ok(out)
+    }
+}
+
+fn format_elements(
+    elements: &[Element], lang: Language, args: &HashMap<String, Value>, out: &mut String,
+) -> Result<(), TranslationError> {
+    for element in elements {
+        match element {
+            Element::Text(text) | Element::Literal(text) => out.push_str(text),
+            Element::Variable(name) => out.push_str(&format_value(lang, variable(args, name)?)),
+            Element::Function(function, name) => {
+                let value = variable(args, name)?;
+                let number = value
+                    .as_f64()
+                    .ok_or_else(|| TranslationError::MissingVariable(name.clone()))?;
+                match function {
+                    Function::Duration => out
+                        .push_str(&lang.format_duration(Duration::from_secs_f64(number.max(0.0)))),
+                    Function::Number => out.push_str(&lang.format_number(number)),
+                }
+            },
+            Element::Select {
+                selector,
+                variants,
+                default,
+            } => {
+                let value = variable(args, selector)?;
+                let variant = select(lang, value, variants).unwrap_or(&variants[*default]);
+                format_elements(&variant.value, lang, args, out)?;
+            },
+        }
+    }
+    Ok(())
+}
+
+fn variable<'a>(
+    args: &'a HashMap<String, Value>, name: &str,
+) -> Result<&'a Value, TranslationError> {
+    args.get(name)
+        .ok_or_else(|| TranslationError::MissingVariable(name.to_string()))
+}
+
+fn format_value(lang: Language, value: &Value) -> String {
+    match value {
+        Value::Number(number) => match number.as_f64() {
+            Some(number) => lang.format_number(number),
+            None => number.to_string(),
+        },
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+/// Variant matching a value, exact numbers win over plural categories.
+fn select<'a>(lang: Language, value: &Value, variants: &'a [Variant]) -> Option<&'a Variant> {
+    match value {
+        Value::Number(number) => {
+            let number = number.as_f64()?;
+            let category = lang.plural_category(number);
+            variants
+                .iter()
+                .find(|variant| variant.key == VariantKey::Number(number))
+                .or_else(|| {
+                    variants
+                        .iter()
+                        .find(|variant| variant.key == VariantKey::Name(category.to_string()))
+                })
+        },
+        Value::String(text) => variants
+            .iter()
+            .find(|variant| variant.key == VariantKey::Name(text.clone())),
+        Value::Bool(value) => variants
+            .iter()
+            .find(|variant| variant.key == VariantKey::Name(value.to_string())),
+        _ => None,
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn error(&self, reason: &'static str) -> SyntaxError {
+        SyntaxError {
+            position: self.position,
+            reason,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.position + i) == Some(&c))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().map_or(false, char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char, reason: &'static str) -> Result<(), SyntaxError> {
+        if self.peek() != Some(c) {
+            return Err(self.error(reason));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    /// Text and placeables until the end of the message, or until the end of
+    /// the variant when parsing one.
+    fn pattern(&mut self, in_variant: bool) -> Result<Vec<Element>, SyntaxError> {
+        let mut elements = Vec::new();
+        let mut text = String::new();
+
+        while let Some(c) = self.peek() {
+            if c == '}' || (in_variant && (c == '[' || self.starts_with("*["))) {
+                break;
+            }
+            if c == '{' {
+                if !text.is_empty() {
+                    elements.push(Element::Text(std::mem::take(&mut text)));
+                }
+                elements.push(self.placeable()?);
+            } else {
+                text.push(c);
+                self.position += 1;
+            }
+        }
+        if !text.is_empty() {
+            elements.push(Element::Text(text));
+        }
+
+        if in_variant {
+            trim(&mut elements);
+        }
+        Ok(elements)
+    }
+
+    fn placeable(&mut self) -> Result<Element, SyntaxError> {
+        self.expect('{', "Expected `{`")?;
+        self.skip_whitespace();
+
+        let element = match self.peek() {
+            Some('$') => {
+                self.position += 1;
+                let name = self.identifier()?;
+                self.skip_whitespace();
+                if self.starts_with("->") {
+                    self.position += 2;
+                    self.select(name)?
+                } else {
+                    Element::Variable(name)
+                }
+            },
+            Some('"') => Element::Literal(self.string()?),
+            Some(c) if c.is_ascii_uppercase() => self.function()?,
+            _ => return Err(self.error("Expected a variable or a string")),
+        };
+
+        self.skip_whitespace();
+        self.expect('}', "Expected `}`")?;
+        Ok(element)
+    }
+
+    fn select(&mut self, selector: String) -> Result<Element, SyntaxError> {
+        let mut variants = Vec::new();
+        let mut default = None;
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('}') => break,
+                Some('*') => {
+                    if default.is_some() {
+                        return Err(self.error("Only one variant can be the default"));
+                    }
+                    default = Some(variants.len());
+                    self.position += 1;
+                },
+                Some('[') => {},
+                _ => return Err(self.error("Expected a variant")),
+            }
+
+            self.expect('[', "Expected `[`")?;
+            self.skip_whitespace();
+            let mut key = String::new();
+            while let Some(c) = self.peek().filter(|c| *c != ']') {
+                key.push(c);
+                self.position += 1;
+            }
+            self.expect(']', "Expected `]`")?;
+
+            let key = key.trim();
+            let key = match key.parse::<f64>() {
+                Ok(number) => VariantKey::Number(number),
+                Err(_) if is_identifier(key) => VariantKey::Name(key.to_string()),
+                Err(_) => return Err(self.error("Invalid variant key")),
+            };
+            let value = self.pattern(true)?;
+            variants.push(Variant { key, value });
+        }
+
+        let default = default.ok_or_else(|| self.error("Missing default variant"))?;
+        Ok(Element::Select {
+            selector,
+            variants,
+            default,
+        })
+    }
+
+    fn function(&mut self) -> Result<Element, SyntaxError> {
+        let function = match self.identifier()?.as_str() {
+            "DURATION" => Function::Duration,
+            "NUMBER" => Function::Number,
+            _ => return Err(self.error("Unknown function")),
+        };
+        self.expect('(', "Expected `(`")?;
+        self.skip_whitespace();
+        self.expect('$', "Expected a variable")?;
+        let name = self.identifier()?;
+        self.skip_whitespace();
+        self.expect(')', "Expected `)`")?;
+        Ok(Element::Function(function, name))
+    }
+
+    fn identifier(&mut self) -> Result<String, SyntaxError> {
+        let mut name = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        {
+            name.push(c);
+            self.position += 1;
+        }
+        if name.is_empty() {
+            return Err(self.error("Expected a variable name"));
+        }
+        Ok(name)
+    }
+
+    fn string(&mut self) -> Result<String, SyntaxError> {
+        self.expect('"', "Expected `\"`")?;
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                Some('"') => break,
+                Some('\\') => {
+                    self.position += 1;
+                    let c = self
+                        .peek()
+                        .ok_or_else(|| self.error("Unterminated string"))?;
+                    text.push(c);
+                },
+                Some(c) => text.push(c),
+                None => return Err(self.error("Unterminated string")),
+            }
+            self.position += 1;
+        }
+        self.position += 1;
+        Ok(text)
+    }
+}
+
+fn is_identifier(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Remove the whitespace around a variant value.
+fn trim(elements: &mut Vec<Element>) {
+    if let Some(Element::Text(text)) = elements.first_mut() {
+        *text = text.trim_start().to_string();
+    }
+    if let Some(Element::Text(text)) = elements.last_mut() {
+        *text = text.trim_end().to_string();
+    }
+    elements.retain(|element| !matches!(element, Element::Text(text) if text.is_empty()));
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+
+    fn format(lang: &'static str, source: &str, args: Value) -> String {
+        let args = HashMap::<String, Value>::deserialize(args).expect("valid arguments");
+        Message::parse(source)
+            .expect("valid message")
+            .format(Language(lang), &args)
+            .expect("formatted message")
+    }
+
+    fn error(source: &str) -> &'static str {
+        Message::parse(source).expect_err("invalid message").reason
+    }
+
+    #[test]
+    fn text() {
+        let message = Message::parse(r#"Hi { "{" }there{ "}" }"#).expect("valid message");
+        assert_eq!(message.text(), "Hi {there}");
+
+        let message = Message::parse("Hi { $name }").expect("valid message");
+        assert_eq!(message.text(), "Hi { $name }");
+    }
+
+    #[test]
+    fn variables() {
+        assert_eq!(
+            format(
+                "en",
+                "{ $user } played { $count }",
+                json!({ "user": "Ann", "count": 12345 })
+            ),
+            "Ann played 12,345"
+        );
+        assert_eq!(
+            format("es", "{ NUMBER($n) }", json!({ "n": 12345.5 })),
+            "12.345,5"
+        );
+    }
+
+    #[test]
+    fn missing_variable() {
+        let message = Message::parse("{ $count }").expect("valid message");
+        let result = message.format(Language("en"), &HashMap::new());
+        assert!(matches!(result, Err(TranslationError::MissingVariable(name)) if name == "count"));
+    }
+
+    #[test]
+    fn variants() {
+        let source = "{ $n -> [0] no songs [one] one song *[other] { $n } songs }";
+        assert_eq!(format("en", source, json!({ "n": 0 })), "no songs");
+        assert_eq!(format("en", source, json!({ "n": 1 })), "one song");
+        assert_eq!(format("en", source, json!({ "n": 2 })), "2 songs");
+        assert_eq!(format("en", source, json!({ "n": 1.5 })), "1.5 songs");
+
+        let source = "{ $kind -> [album] an album [playlist] a playlist *[other] a song }";
+        assert_eq!(format("en", source, json!({ "kind": "album" })), "an album");
+        assert_eq!(format("en", source, json!({ "kind": "video" })), "a song");
+    }
+
+    #[test]
+    fn exact_numbers_win() {
+        let source = "{ $n -> [one] one song [1] a single song *[other] songs }";
+        assert_eq!(format("en", source, json!({ "n": 1 })), "a single song");
+    }
+
+    #[test]
+    fn many_category() {
+        let source =
+            "{ $n -> [one] una canción [many] { $n } de canciones *[other] { $n } canciones }";
+        assert_eq!(format("es", source, json!({ "n": 1 })), "una canción");
+        assert_eq!(
+            format("es", source, json!({ "n": 1_000_000 })),
+            "1.000.000 de canciones"
+        );
+        assert_eq!(
+            format("es", source, json!({ "n": 12345 })),
+            "12.345 canciones"
+        );
+        assert_eq!(
+            format("en", source, json!({ "n": 1_000_000 })),
+            "1,000,000 canciones"
+        );
+    }
+
+    #[test]
+    fn variant_whitespace() {
+        let source = "{ $n -> [one] { \" \" }a *[other]   b   }!";
+        assert_eq!(format("en", source, json!({ "n": 1 })), " a!");
+        assert_eq!(format("en", source, json!({ "n": 2 })), "b!");
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_eq!(error("a } b"), "Unexpected `}`");
+        assert_eq!(error("{ $n -> [one] a }"), "Missing default variant");
+        assert_eq!(
+            error("{ $n -> *[one] a *[other] b }"),
+            "Only one variant can be the default"
+        );
+        assert_eq!(error("{ $n -> [a b] x *[other] y }"), "Invalid variant key");
+        assert_eq!(error("{ UPPER($n) }"), "Unknown function");
+        assert_eq!(error("{ NUMBER($n }"), "Expected `)`");
+        assert_eq!(error("{ \"text }"), "Unterminated string");
+        assert_eq!(error("{ name }"), "Expected a variable or a string");
+        assert_eq!(error("{ $n"), "Expected `}`");
+
+        let err = Message::parse("ok { $ }").expect_err("invalid message");
+        assert_eq!(err.position, 6);
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::lazy::SyncLazy;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::{Duration, SystemTime};
+
+use tracing::{error, info, warn};
+
+use super::message::Message;
+use super::{Language, TranslationError};
+
+/// Directory with the translations, one `<locale>.json` file per language.
+pub const I18N_DIR: &str = "i18n";
+
+/// Translations of a language.
+pub struct Locale {
+    /// Name of the language in itself, from its `language.name` message.
+    pub name: String,
+    pub messages: HashMap<String, Message>,
+}
+
+type Locales = HashMap<Language, Locale>;
+
+/// Translations in use, read from [`I18N_DIR`] when first used. Reloading
+/// replaces the map instead of changing it, so readers holding the previous
+/// one keep using it until they are done.
+static LOCALES: SyncLazy<RwLock<Arc<Locales>>> = SyncLazy::new(|| {
+    let (locales, errors) = read_locales(Path::new(I18N_DIR));
+    for err in errors {
+        error!("{}", err);
+    }
+    if !locales.contains_key(&Language::default()) {
+        warn!(
+            "Missing translations for the default language, expected {}/{}.json",
+            I18N_DIR,
+            Language::default()
+        );
+    }
+    info!("Loaded {} languages", locales.len());
+    RwLock::new(Arc::new(locales))
+});
+
+/// How often the translation files are checked for changes when watching
+/// them.
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Locale codes seen so far. They are leaked once, so languages can be
+/// `Copy`.
+static CODES: SyncLazy<Mutex<HashSet<&'static str>>> = SyncLazy::new(|| Mutex::new(HashSet::new()));
+
+pub(super) fn intern(code: &str) -> &'static str {
+    let mut codes = CODES.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(code) = codes.get(code) {
+        return code;
+    }
+    let code: &'static str = Box::leak(code.to_string().into_boxed_str());
+    codes.insert(code);
+    code
+}
+
+/// Canonical form of a locale code, like `es-MX` for `es_mx`.
+pub(super) fn normalize(code: &str) -> String {
+    code.trim()
+        .split(|c| c == '-' || c == '_')
+        .enumerate()
+        .map(|(i, part)| match i {
+            0 => part.to_lowercase(),
+            _ if part.len() == 2 => part.to_uppercase(),
+            _ => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+pub(super) fn locales() -> Arc<Locales> {
+    Arc::clone(&LOCALES.read().unwrap_or_else(PoisonError::into_inner))
+}
+
+/// Read the translation files again. The translations in use are only
+/// replaced when every file is valid, otherwise they are kept and the errors
+/// are returned.
+pub fn reload() -> Result<Vec<Language>, Vec<TranslationError>> {
+    let (locales, mut errors) = read_locales(Path::new(I18N_DIR));
+    if !locales.contains_key(&Language::default()) {
+        errors.push(TranslationError::MissingLanguage(Language::default()));
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut languages: Vec<Language> = locales.keys().copied().collect();
+    languages.sort();
+    *LOCALES.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(locales);
+    info!("Reloaded {} languages", languages.len());
+    Ok(languages)
+}
+
+/// Reload the translations when a file in [`I18N_DIR`] changes, in the
+/// background.
+pub fn spawn_watcher() {
+    tokio::spawn(async {
+        let dir = Path::new(I18N_DIR);
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+        let mut last = modified(dir);
+        loop {
+            interval.tick().await;
+            let current = modified(dir);
+            if current == last {
+                continue;
+            }
+            last = current;
+
+            if let Err(errors) = reload() {
+                for err in errors {
+                    error!("Failed to reload translations: {}", err);
+                }
+            }
+        }
+    });
+}
+
+/// Modification time of every translation file in `dir`.
+fn modified(dir: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let mut files: Vec<_> = translation_files(dir)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|path| {
+            let time = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, time)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn translation_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let files = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .collect();
+    Ok(files)
+}
+
+/// Every language in `dir`, and the errors of the files that could not be
+/// read.
+fn read_locales(dir: &Path) -> (Locales, Vec<TranslationError>) {
+    let mut locales = HashMap::new();
+    let mut errors = Vec::new();
+    let files = match translation_files(dir) {
+        Ok(files) => files,
+        Err(err) => {
+            errors.push(TranslationError::File(
+                dir.display().to_string(),
+                Box::new(err.into()),
+            ));
+            return (locales, errors);
+        },
+    };
+
+    for path in files {
+        let code = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(code) => code,
+            None => continue,
+        };
+        match load_locale(&path) {
+            Ok(locale) => {
+                locales.insert(Language::from_code(code), locale);
+            },
+            Err(err) => errors.push(TranslationError::File(
+                path.display().to_string(),
+                Box::new(err),
+            )),
+        }
+    }
+    (locales, errors)
+}
+
+fn load_locale(path: &Path) -> Result<Locale, TranslationError> {
+    let reader = BufReader::new(File::open(path)?);
+    let sources: HashMap<String, String> = serde_json::from_reader(reader)?;
+
+    let messages = sources
+        .into_iter()
+        .map(|(key, source)| match Message::parse(&source) {
+            Ok(message) => Ok((key, message)),
+            Err(err) => Err(TranslationError::Syntax(key, err)),
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    let name = messages
+        .get("language.name")
+        .map(|message| message.text().to_string())
+        .or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+
+    Ok(Locale { name, messages })
+}
@@ -12,23 +12,28 @@ use std::env::VarError;
 use std::error::Error;
 use std::sync::Arc;
 
+use lyrics::Lyrics;
 use mimalloc::MiMalloc;
 use serenity::client::bridge::gateway::{GatewayIntents, ShardManager};
 use serenity::client::Client;
 use serenity::framework::StandardFramework;
 use serenity::http::Http;
-use serenity::model::id::UserId;
+use serenity::model::id::{ApplicationId, GuildId, UserId};
 use serenity::prelude::*;
 use songbird::SerenityInit;
 
 mod audio;
+mod checks;
 mod commands;
 mod constants;
 mod database;
 mod events;
 mod hooks;
+mod invocation;
 mod lang;
 mod paginator;
+mod prefixes;
+mod ratelimit;
 mod utils;
 
 #[global_allocator]
@@ -43,9 +48,16 @@ impl TypeMapKey for ShardManagerContainer {
     type Value = Arc<Mutex<ShardManager>>;
 }
 
-async fn init_data_manager(client: &Client) {
+pub struct LyricsContainer;
+
+impl TypeMapKey for LyricsContainer {
+    type Value = Arc<Lyrics>;
+}
+
+async fn init_data_manager(client: &Client, lyrics: Lyrics) {
     let mut data = client.data.write().await;
     data.insert::<ShardManagerContainer>(Arc::clone(&client.shard_manager));
+    data.insert::<LyricsContainer>(Arc::new(lyrics));
 }
 
 #[tokio::main]
@@ -59,45 +71,67 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let token = env::var("DISCORD_TOKEN").map_err(env_var_err)?;
     let prefix = env::var("BOT_PREFIX").map_err(env_var_err)?;
     let database = env::var("DATABASE_URI").map_err(env_var_err)?;
+    let lyrics = Lyrics::from_env()?;
+    let slash_commands_guild = match env::var("SLASH_COMMANDS_GUILD") {
+        Ok(id) if !id.is_empty() => Some(GuildId(id.parse()?)),
+        _ => None,
+    };
+
+    let watch_translations = matches!(
+        env::var("WATCH_TRANSLATIONS").as_deref(),
+        Ok("1") | Ok("true")
+    );
 
-    let owners = get_owners(&token).await?;
+    let (application_id, bot_id, owners) = get_application(&token).await?;
+    prefixes::init(prefix)?;
 
     // Connect to database
     database::connect(&database).await?;
 
+    if watch_translations {
+        lang::registry::spawn_watcher();
+    }
+
     let mut client = Client::builder(&token)
-        .event_handler(events::Handler)
+        .application_id(application_id.0)
+        .event_handler(events::Handler {
+            slash_commands_guild,
+        })
         .framework(
             StandardFramework::new()
                 .configure(|c| {
-                    c.prefix(&prefix)
+                    // Prefixes are resolved per guild by the dynamic prefix hook
+                    c.prefix("")
+                        .dynamic_prefix(hooks::dynamic_prefix)
+                        .on_mention(Some(bot_id))
                         .no_dm_prefix(true)
                         .case_insensitivity(true)
                         .allow_dm(true)
                         .owners(owners)
                 })
+                .before(hooks::before_hook)
                 .after(hooks::after_hook)
+                .on_dispatch_error(hooks::dispatch_error)
                 .help(&commands::help::HELP)
                 .group(&commands::AUDIO_GROUP)
-                .group(&commands::GENERAL_GROUP)
-                .bucket("basic", |b| {
-                    b.time_span(10).limit(4).delay_action(hooks::delay_action)
-                })
-                .await,
+                .group(&commands::GENERAL_GROUP),
         )
         .register_songbird()
         .intents(GatewayIntents::non_privileged())
         .await?;
 
-    init_data_manager(&client).await;
+    init_data_manager(&client, lyrics).await;
     client.start_autosharded().await?;
     Ok(())
 }
 
-/// Fetch bot owners from Discord application
-async fn get_owners(token: &str) -> Result<HashSet<UserId>, serenity::Error> {
+/// Fetch application id, bot user id and bot owners from Discord application
+async fn get_application(
+    token: &str,
+) -> Result<(ApplicationId, UserId, HashSet<UserId>), serenity::Error> {
     let http = Http::new_with_token(token);
     let info = http.get_current_application_info().await?;
+    let bot_id = http.get_current_user().await?.id;
 
     let mut owners = HashSet::new();
     if let Some(team) = info.team {
@@ -105,5 +139,5 @@ async fn get_owners(token: &str) -> Result<HashSet<UserId>, serenity::Error> {
     } else {
         owners.insert(info.owner.id);
     }
-    Ok(owners)
+    Ok((ApplicationId(info.id.0), bot_id, owners))
 }
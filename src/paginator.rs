@@ -1,107 +1,397 @@
 use std::time::Duration;
 
-use async_recursion::async_recursion;
 use serde_json::json;
-use serenity::builder::CreateEmbed;
+use serenity::builder::{CreateComponents, CreateEmbed};
 use serenity::client::Context;
 use serenity::framework::standard::CommandResult;
-use serenity::model::channel::Message;
+use serenity::model::channel::{Message, ReactionType};
+use serenity::model::interactions::message_component::{ButtonStyle, MessageComponentInteraction};
+use serenity::model::interactions::{
+    InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
+};
 use serenity::utils::Colour;
+use tracing::debug;
 
-use crate::database::get_language;
-
-fn create_embed<'a>(
-    e: &'a mut CreateEmbed, msg: &Message, title: &str, description: &str, thumbnail: &str,
-    footer: &str,
-) -> &'a mut CreateEmbed {
-    e.author(|a| {
-        a.name(&msg.author.name);
-        a.icon_url(
-            msg.author
-                .avatar_url()
-                .unwrap_or_else(|| msg.author.default_avatar_url()),
-        );
-        a
-    });
-    e.thumbnail(thumbnail);
-    e.color(Colour::DARK_PURPLE);
-    e.title(&title);
-    e.description(description);
-    e.footer(|f| f.text(footer));
-    e
+use crate::invocation::Invocation;
+use crate::lang::{Language, LocalizedError};
+
+/// Longest embed description Discord accepts, in characters.
+pub const MAX_DESCRIPTION: usize = 4096;
+
+/// Longest embed field value Discord accepts, in characters.
+pub const MAX_FIELD: usize = 1024;
+
+/// How long the controls keep working after the last press.
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How long to wait for the page number after pressing jump.
+const JUMP_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Control {
+    First,
+    Previous,
+    Jump,
+    Next,
+    Last,
+    Close,
 }
 
-#[async_recursion]
-async fn _send_page(
-    title: String, pages: Vec<String>, thumbnail: String, page: usize, ctx: &Context,
-    msg: &Message, paginated: Option<Message>,
-) -> CommandResult {
-    let lang = get_language(msg.author.id, msg.guild_id).await;
-    let page_number = lang.translate(
-        "page.number",
-        json!({ "number": page + 1, "total": pages.len() }),
-    )?;
-
-    let react_msg = if let Some(mut paginated) = paginated {
-        if let Some(page) = pages.get(page) {
-            paginated
+impl Control {
+    const NAVIGATION: [Control; 5] = [
+        Control::First,
+        Control::Previous,
+        Control::Jump,
+        Control::Next,
+        Control::Last,
+    ];
+
+    fn id(self) -> &'static str {
+        match self {
+            Control::First => "page.first",
+            Control::Previous => "page.previous",
+            Control::Jump => "page.jump",
+            Control::Next => "page.next",
+            Control::Last => "page.last",
+            Control::Close => "page.close",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Self> {
+        Self::NAVIGATION
+            .iter()
+            .copied()
+            .chain(std::iter::once(Control::Close))
+            .find(|control| control.id() == id)
+    }
+
+    fn emoji(self) -> &'static str {
+        match self {
+            Control::First => "\u{23ee}",
+            Control::Previous => "\u{25c0}",
+            Control::Jump => "\u{1f522}",
+            Control::Next => "\u{25b6}",
+            Control::Last => "\u{23ed}",
+            Control::Close => "\u{2716}",
+        }
+    }
+
+    fn enabled(self, page: usize, total: usize) -> bool {
+        match self {
+            Control::First | Control::Previous => page > 0,
+            Control::Next | Control::Last => page + 1 < total,
+            Control::Jump => total > 2,
+            Control::Close => true,
+        }
+    }
+}
+
+/// Embed pages navigated with buttons. Only the user who invoked the command
+/// can turn the pages, and the buttons are disabled once nobody used them
+/// for a while.
+pub struct Paginator {
+    pages: Vec<CreateEmbed>,
+    footer: Option<String>,
+    start: usize,
+}
+
+impl Paginator {
+    pub fn new(pages: Vec<CreateEmbed>) -> Self {
+        Self {
+            pages,
+            footer: None,
+            start: 0,
+        }
+    }
+
+    /// Footer shown on every page, before the page number.
+    pub fn footer(mut self, footer: String) -> Self {
+        self.footer = Some(footer);
+        self
+    }
+
+    /// Page shown first, the first page by default.
+    pub fn start(mut self, page: usize) -> Self {
+        self.start = page;
+        self
+    }
+
+    fn render<'a>(
+        &self, e: &'a mut CreateEmbed, lang: Language, page: usize,
+    ) -> &'a mut CreateEmbed {
+        if let Some(embed) = self.pages.get(page) {
+            *e = embed.clone();
+        }
+        let mut footer: Vec<String> = self.footer.iter().cloned().collect();
+        if self.pages.len() > 1 {
+            let number = lang
+                .translate(
+                    "page.number",
+                    json!({ "number": page + 1, "total": self.pages.len() }),
+                )
+                .unwrap_or_default();
+            footer.push(number);
+        }
+        if !footer.is_empty() {
+            e.footer(|f| f.text(footer.join(" \u{2022} ")));
+        }
+        e
+    }
+
+    fn components<'a>(
+        &self, c: &'a mut CreateComponents, page: usize, active: bool,
+    ) -> &'a mut CreateComponents {
+        let total = self.pages.len();
+        if total < 2 {
+            return c;
+        }
+
+        c.create_action_row(|row| {
+            for control in Control::NAVIGATION.iter().copied() {
+                row.create_button(|b| {
+                    b.custom_id(control.id());
+                    b.style(ButtonStyle::Secondary);
+                    b.emoji(ReactionType::Unicode(control.emoji().into()));
+                    b.disabled(!active || !control.enabled(page, total));
+                    b
+                });
+            }
+            row
+        });
+        c.create_action_row(|row| {
+            row.create_button(|b| {
+                b.custom_id(Control::Close.id());
+                b.style(ButtonStyle::Danger);
+                b.emoji(ReactionType::Unicode(Control::Close.emoji().into()));
+                b.disabled(!active);
+                b
+            })
+        })
+    }
+
+    /// Send the first page and turn pages on button presses until the
+    /// controls time out or the message is closed.
+    pub async fn send(self, ctx: &Context, invocation: &Invocation<'_>) -> CommandResult {
+        if self.pages.is_empty() {
+            return Err(LocalizedError::new("error.no_pages").into());
+        }
+        let lang = invocation.language().await;
+        let author = invocation.author().id;
+        let total = self.pages.len();
+        let mut page = self.start.min(total - 1);
+
+        let mut message = invocation
+            .send_embed_with_components(
+                ctx,
+                |e| self.render(e, lang, page),
+                |c| self.components(c, page, true),
+            )
+            .await?;
+        if total < 2 {
+            return Ok(());
+        }
+
+        while let Some(interaction) = message
+            .await_component_interaction(ctx)
+            .author_id(author)
+            .timeout(TIMEOUT)
+            .await
+        {
+            let control = match Control::from_id(&interaction.data.custom_id) {
+                Some(control) => control,
+                None => continue,
+            };
+
+            page = match control {
+                Control::First => 0,
+                Control::Previous => page.saturating_sub(1),
+                Control::Next => (page + 1).min(total - 1),
+                Control::Last => total - 1,
+                Control::Jump => ask_page(ctx, &interaction, lang, total)
+                    .await?
+                    .unwrap_or(page),
+                Control::Close => {
+                    acknowledge(ctx, &interaction).await?;
+                    message.delete(ctx).await?;
+                    return Ok(());
+                },
+            };
+            if control != Control::Jump {
+                acknowledge(ctx, &interaction).await?;
+            }
+
+            message
                 .edit(ctx, |m| {
-                    m.embed(|e| create_embed(e, msg, &title, &page, &thumbnail, &page_number));
+                    m.embed(|e| self.render(e, lang, page));
+                    m.components(|c| self.components(c, page, true));
                     m
                 })
                 .await?;
         }
-        paginated
-    } else {
-        let page = pages.get(0).ok_or("Failed to get first page")?;
-        msg.channel_id
-            .send_message(&ctx.http, |m| {
-                m.reference_message(msg);
-                m.allowed_mentions(|f| f.replied_user(false));
-                m.embed(|e| create_embed(e, msg, &title, &page, &thumbnail, &page_number));
-                m
-            })
-            .await?
-    };
 
-    react_msg.delete_reactions(ctx).await?;
-
-    if page > 0 {
-        react_msg.react(ctx, '\u{2b05}').await?;
-    }
-    if page + 1 < pages.len() {
-        react_msg.react(ctx, '\u{27a1}').await?;
+        // Leave the last page with its controls disabled
+        message
+            .edit(ctx, |m| m.components(|c| self.components(c, page, false)))
+            .await?;
+        Ok(())
     }
+}
 
-    if let Some(reaction) = &react_msg
-        .await_reaction(&ctx)
-        .timeout(Duration::from_secs(120))
-        .author_id(msg.author.id)
+async fn acknowledge(
+    ctx: &Context, interaction: &MessageComponentInteraction,
+) -> Result<(), serenity::Error> {
+    interaction
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::DeferredUpdateMessage)
+        })
         .await
-    {
-        let emoji = &reaction.as_inner_ref().emoji;
-
-        match emoji.as_data().as_str() {
-            "\u{2b05}" => {
-                if page > 0 {
-                    _send_page(title, pages, thumbnail, page - 1, ctx, msg, Some(react_msg))
-                        .await?;
-                }
-            },
-            "\u{27a1}" => {
-                if page + 1 < pages.len() {
-                    _send_page(title, pages, thumbnail, page + 1, ctx, msg, Some(react_msg))
-                        .await?;
-                }
-            },
-            _ => {},
+}
+
+/// Ask the user for a page number, and wait for them to send it. The number
+/// is deleted when possible to keep the channel clean.
+async fn ask_page(
+    ctx: &Context, interaction: &MessageComponentInteraction, lang: Language, total: usize,
+) -> Result<Option<usize>, serenity::Error> {
+    let question = lang
+        .translate("page.jump", json!({ "total": total }))
+        .unwrap_or_default();
+    interaction
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource);
+            r.interaction_response_data(|d| {
+                d.content(question);
+                d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+                d
+            });
+            r
+        })
+        .await?;
+
+    let reply = interaction
+        .channel_id
+        .await_reply(ctx)
+        .author_id(interaction.user.id)
+        .timeout(JUMP_TIMEOUT)
+        .await;
+    let reply = match reply {
+        Some(reply) => reply,
+        None => return Ok(None),
+    };
+
+    let number = reply.content.trim().parse::<usize>().ok();
+    if let Err(err) = reply.delete(ctx).await {
+        debug!("Failed to delete page number message: {:?}", err);
+    }
+    Ok(number
+        .filter(|number| (1..=total).contains(number))
+        .map(|number| number - 1))
+}
+
+/// Join lines into pages of at most `limit` characters. Lines longer than
+/// the limit are split across pages.
+pub fn split_pages<'a, I>(lines: I, limit: usize) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a str>, {
+    let mut pages = Vec::new();
+    let mut page = String::new();
+    let mut length = 0;
+
+    for line in lines {
+        let mut chars: Vec<char> = line.chars().collect();
+        while chars.len() > limit {
+            if !page.is_empty() {
+                pages.push(std::mem::take(&mut page));
+                length = 0;
+            }
+            let rest = chars.split_off(limit);
+            pages.push(chars.into_iter().collect());
+            chars = rest;
+        }
+
+        if !page.is_empty() && length + 1 + chars.len() > limit {
+            pages.push(std::mem::take(&mut page));
+            length = 0;
         }
+        if !page.is_empty() {
+            page.push('\n');
+            length += 1;
+        }
+        length += chars.len();
+        page.extend(chars);
+    }
+    if !page.is_empty() {
+        pages.push(page);
     }
-    Ok(())
+    pages
 }
 
+/// Embed pages with a title and thumbnail, for the text pages of a command.
 pub async fn send_pages(
-    title: String, pages: Vec<String>, thumbnail: String, ctx: &Context, msg: &Message,
+    title: String, pages: Vec<String>, thumbnail: String, ctx: &Context,
+    invocation: &Invocation<'_>,
 ) -> CommandResult {
-    _send_page(title, pages, thumbnail, 0, ctx, msg, None).await
+    let pages = pages
+        .iter()
+        .map(|page| {
+            let mut e = CreateEmbed::default();
+            invocation.embed_author(&mut e);
+            e.thumbnail(&thumbnail);
+            e.color(Colour::DARK_PURPLE);
+            e.title(&title);
+            e.description(page);
+            e
+        })
+        .collect();
+    Paginator::new(pages).send(ctx, invocation).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_pages;
+
+    #[test]
+    fn joins_lines() {
+        assert_eq!(split_pages(vec!["ab", "cd"], 10), vec!["ab\ncd"]);
+        assert_eq!(split_pages(Vec::new(), 10), Vec::<String>::new());
+    }
+
+    #[test]
+    fn long_lines() {
+        assert_eq!(split_pages(vec!["abcdefg"], 3), vec!["abc", "def", "g"]);
+        assert_eq!(split_pages(vec!["ab", "cdefgh", "i"], 4), vec![
+            "ab", "cdef", "gh\ni"
+        ]);
+    }
+
+    #[test]
+    fn line_at_limit() {
+        assert_eq!(split_pages(vec!["abcd"], 4), vec!["abcd"]);
+        assert_eq!(split_pages(vec!["a", "bcde"], 4), vec!["a", "bcde"]);
+        assert_eq!(split_pages(vec!["abcdefgh"], 4), vec!["abcd", "efgh"]);
+    }
+
+    #[test]
+    fn newline_counted() {
+        // "ab\ncd" is 5 characters long
+        assert_eq!(split_pages(vec!["ab", "cd"], 5), vec!["ab\ncd"]);
+        assert_eq!(split_pages(vec!["ab", "cd"], 4), vec!["ab", "cd"]);
+        assert_eq!(split_pages(vec!["ab", "c", "d"], 5), vec!["ab\nc", "d"]);
+    }
+
+    #[test]
+    fn blank_lines() {
+        assert_eq!(split_pages(vec!["a", "", "b"], 10), vec!["a\n\nb"]);
+        // Pages don't start with blank lines
+        assert_eq!(split_pages(vec!["", "a"], 10), vec!["a"]);
+        assert_eq!(split_pages(vec!["abc", "", "d"], 3), vec!["abc", "d"]);
+    }
+
+    #[test]
+    fn multibyte_characters() {
+        assert_eq!(split_pages(vec!["ñandú"], 5), vec!["ñandú"]);
+        assert_eq!(split_pages(vec!["日本語の歌"], 2), vec![
+            "日本", "語の", "歌"
+        ]);
+        assert_eq!(split_pages(vec!["😀😀", "é"], 4), vec!["😀😀\né"]);
+    }
 }
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::lazy::{SyncLazy, SyncOnceCell};
+use std::sync::Arc;
+
+use serde_json::json;
+use serenity::model::id::GuildId;
+use serenity::prelude::RwLock;
+
+use crate::database;
+use crate::lang::LocalizedError;
+
+/// Most prefixes a guild can have.
+pub const MAX_PREFIXES: usize = 5;
+
+/// Longest allowed prefix.
+pub const MAX_PREFIX_LENGTH: usize = 10;
+
+static DEFAULT_PREFIX: SyncOnceCell<String> = SyncOnceCell::new();
+
+/// Custom prefixes of every guild seen, so they are not fetched from the
+/// database on every message. Prefixes are only changed through
+/// [`set_prefixes`], which keeps this up to date.
+static PREFIXES: SyncLazy<RwLock<HashMap<GuildId, Arc<Vec<String>>>>> =
+    SyncLazy::new(|| RwLock::new(HashMap::new()));
+
+pub fn init(prefix: String) -> Result<(), Box<dyn Error>> {
+    DEFAULT_PREFIX
+        .set(prefix)
+        .map_err(|_| "Default prefix already initialized")?;
+    Ok(())
+}
+
+/// Prefix set with `BOT_PREFIX`, used by guilds without custom prefixes.
+pub fn default_prefix() -> &'static str {
+    DEFAULT_PREFIX
+        .get()
+        .expect("Default prefix was not initialized")
+}
+
+/// Prefixes accepted in a guild, the default prefix when it has none.
+pub async fn get_prefixes(guild_id: GuildId) -> Arc<Vec<String>> {
+    if let Some(prefixes) = PREFIXES.read().await.get(&guild_id) {
+        return prefixes.clone();
+    }
+
+    let prefixes = database::get_guild(guild_id)
+        .await
+        .map(|guild| guild.prefixes)
+        .filter(|prefixes| !prefixes.is_empty())
+        .unwrap_or_else(|| vec![default_prefix().to_string()]);
+    let prefixes = Arc::new(prefixes);
+    PREFIXES.write().await.insert(guild_id, prefixes.clone());
+    prefixes
+}
+
+/// Replace the custom prefixes of a guild. An empty list restores the
+/// default prefix.
+pub async fn set_prefixes(
+    guild_id: GuildId, prefixes: Vec<String>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if prefixes.len() > MAX_PREFIXES {
+        return Err(LocalizedError::with(
+            "error.too_many_prefixes",
+            json!({ "max": MAX_PREFIXES }),
+        )
+        .into());
+    }
+    if let Some(prefix) = prefixes
+        .iter()
+        .find(|prefix| prefix.is_empty() || prefix.chars().count() > MAX_PREFIX_LENGTH)
+    {
+        return Err(LocalizedError::with(
+            "error.invalid_prefix",
+            json!({ "prefix": prefix, "max": MAX_PREFIX_LENGTH }),
+        )
+        .into());
+    }
+
+    database::update_guild(guild_id, |guild| guild.prefixes = prefixes).await?;
+    PREFIXES.write().await.remove(&guild_id);
+    Ok(())
+}
+
+/// The prefix `content` starts with, preferring the longest one so `!!` wins
+/// over `!`.
+pub fn find_prefix<'a>(prefixes: &'a [String], content: &str) -> Option<&'a str> {
+    prefixes
+        .iter()
+        .filter(|prefix| content.starts_with(prefix.as_str()))
+        .max_by_key(|prefix| prefix.len())
+        .map(String::as_str)
+}
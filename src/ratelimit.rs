@@ -0,0 +1,68 @@
+use std::collections::{HashMap, VecDeque};
+use std::lazy::SyncLazy;
+use std::time::{Duration, Instant};
+
+use serenity::model::id::UserId;
+use serenity::prelude::Mutex;
+
+/// Users tracked before forgetting the ones that didn't use a bucket lately.
+const PRUNE_THRESHOLD: usize = 1_000;
+
+/// Rate limit of the commands that name it with `#[bucket]`. Limits are
+/// counted per user and shared by prefix commands, slash commands and the
+/// other ways of running them, so switching between them doesn't bypass it.
+#[derive(Debug)]
+pub struct Bucket {
+    name: &'static str,
+    /// Uses allowed during `span`.
+    limit: usize,
+    span: Duration,
+}
+
+/// Commands that query songs or the database, or join voice channels.
+pub static BASIC: Bucket = Bucket {
+    name: "basic",
+    limit: 4,
+    span: Duration::from_secs(10),
+};
+
+static BUCKETS: [&Bucket; 1] = [&BASIC];
+
+/// When every user used every bucket within its span.
+static USES: SyncLazy<Mutex<HashMap<(&'static str, UserId), VecDeque<Instant>>>> =
+    SyncLazy::new(|| Mutex::new(HashMap::new()));
+
+/// Bucket with the name given in `#[bucket]`.
+pub fn find(name: &str) -> Option<&'static Bucket> {
+    BUCKETS.iter().copied().find(|bucket| bucket.name == name)
+}
+
+impl Bucket {
+    /// Count a use of the bucket by a user. When they already used it too
+    /// much, the use is not counted and the time to wait is given instead.
+    pub async fn hit(&self, user_id: UserId) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut uses = USES.lock().await;
+        if uses.len() > PRUNE_THRESHOLD {
+            uses.retain(|(name, _), times| {
+                let span = find(name).map_or(Duration::ZERO, |bucket| bucket.span);
+                times
+                    .back()
+                    .map_or(false, |time| now.duration_since(*time) < span)
+            });
+        }
+
+        let times = uses.entry((self.name, user_id)).or_default();
+        while let Some(time) = times.front() {
+            if now.duration_since(*time) < self.span {
+                break;
+            }
+            times.pop_front();
+        }
+        if let Some(oldest) = times.front().filter(|_| times.len() >= self.limit) {
+            return Err(self.span - now.duration_since(*oldest));
+        }
+        times.push_back(now);
+        Ok(())
+    }
+}
@@ -1,73 +1,68 @@
-use serde_json::Value;
+use std::time::Duration;
+
+use serde_json::{json, Value};
 use serenity::client::Context;
 use serenity::framework::standard::CommandResult;
-use serenity::model::channel::Message;
 use serenity::utils::Colour;
 
-use crate::database::get_language;
+use crate::database::get_user;
+use crate::database::user::ReplyStyle;
+use crate::invocation::Invocation;
+use crate::lang::LocalizedError;
+
+/// Send an info answer the way its author prefers: an embed, or a single
+/// line of text when they chose compact replies.
+async fn send_reply(
+    title: &str, description: &str, invocation: &Invocation<'_>, ctx: &Context,
+) -> CommandResult {
+    let compact = get_user(invocation.author().id)
+        .await
+        .map_or(false, |user| user.reply_style == ReplyStyle::Compact);
+    if compact {
+        invocation
+            .reply(ctx, format!("**{}** {}", title, description))
+            .await?;
+        return Ok(());
+    }
+
+    invocation
+        .send_embed(ctx, |e| {
+            invocation.embed_author(e);
+            e.color(Colour::DARK_BLUE);
+            e.title(title);
+            e.description(description);
+            e
+        })
+        .await?;
+    Ok(())
+}
 
-pub async fn send_info<S>(title: S, description: S, msg: &Message, ctx: &Context) -> CommandResult
+pub async fn send_info<S>(
+    title: S, description: S, invocation: &Invocation<'_>, ctx: &Context,
+) -> CommandResult
 where
     S: AsRef<str>, {
-    let lang = get_language(msg.author.id, msg.guild_id).await;
+    let lang = invocation.language().await;
     let title = lang.get(title.as_ref());
     let description = lang.get(description.as_ref());
 
-    msg.channel_id
-        .send_message(&ctx.http, |m| {
-            m.reference_message(msg);
-            m.allowed_mentions(|f| f.replied_user(false));
-            m.embed(|e| {
-                e.author(|a| {
-                    a.name(&msg.author.name);
-                    a.icon_url(
-                        msg.author
-                            .avatar_url()
-                            .unwrap_or_else(|| msg.author.default_avatar_url()),
-                    );
-                    a
-                });
-                e.color(Colour::DARK_BLUE);
-                e.title(title);
-                e.description(description);
-                e
-            });
-            m
-        })
-        .await?;
-    Ok(())
+    send_reply(&title, &description, invocation, ctx).await
 }
 
 pub async fn send_translated_info<S>(
-    title: S, description: S, data: Value, msg: &Message, ctx: &Context,
+    title: S, description: S, data: Value, invocation: &Invocation<'_>, ctx: &Context,
 ) -> CommandResult
 where
     S: AsRef<str>, {
-    let lang = get_language(msg.author.id, msg.guild_id).await;
+    let lang = invocation.language().await;
     let title = lang.get(title.as_ref());
     let description = lang.translate(description.as_ref(), data)?;
 
-    msg.channel_id
-        .send_message(&ctx.http, |m| {
-            m.reference_message(msg);
-            m.allowed_mentions(|f| f.replied_user(false));
-            m.embed(|e| {
-                e.author(|a| {
-                    a.name(&msg.author.name);
-                    a.icon_url(
-                        msg.author
-                            .avatar_url()
-                            .unwrap_or_else(|| msg.author.default_avatar_url()),
-                    );
-                    a
-                });
-                e.color(Colour::DARK_BLUE);
-                e.title(title);
-                e.description(description);
-                e
-            });
-            m
-        })
-        .await?;
-    Ok(())
+    send_reply(&title, &description, invocation, ctx).await
+}
+
+/// Parse a human readable duration, like `1min 30s`.
+pub fn parse_duration(value: &str) -> Result<Duration, LocalizedError> {
+    humantime::parse_duration(value.trim())
+        .map_err(|_| LocalizedError::with("error.invalid_duration", json!({ "value": value })))
 }